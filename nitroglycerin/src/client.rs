@@ -1,4 +1,15 @@
-use crate::{get::Get, put::Put, query::Query, delete::Delete};
+use crate::{
+    delete::Delete,
+    get::Get,
+    limit::{RateLimit, RateLimited},
+    observe::{Observed, RequestObserver},
+    put::Put,
+    retry::{Retry, RetryPolicy},
+    query::Query,
+    ttl::{self, TimeToLive},
+    update::Increment,
+    Table, TableIndex,
+};
 
 /// Extension trait providing high level implementations of dynamodb requests
 pub trait DynamoDb: rusoto_dynamodb::DynamoDb {
@@ -10,14 +21,48 @@ pub trait DynamoDb: rusoto_dynamodb::DynamoDb {
     fn query<'d, T: Query<'d, Self>>(&'d self) -> T::Builder {
         T::query(self)
     }
-    /// Perform a put item request
+    /// Perform a put item request, checking and bumping the `#[nitro(version)]` of the item and filling in its timestamps
     fn put<'d, T: Put<'d, Self>>(&'d self, t: T) -> T::Builder {
         t.put(self)
     }
-    /// Perform a put item request
+    /// Perform a delete item request
     fn delete<'d, T: Delete<'d, Self>>(&'d self) -> T::Builder {
         T::delete(self)
     }
+    /// Perform an atomic counter update item request
+    fn increment<'d, T: Increment<'d, Self>>(&'d self) -> T::Builder {
+        T::increment(self)
+    }
+    /// Enable time to live on the `#[nitro(ttl)]` attribute of a table
+    fn enable_ttl<T: Table + TimeToLive>(&self) -> ttl::Expr<'_, Self, T> {
+        ttl::Expr::new(self, true)
+    }
+    /// Disable time to live on the `#[nitro(ttl)]` attribute of a table
+    fn disable_ttl<T: Table + TimeToLive>(&self) -> ttl::Expr<'_, Self, T> {
+        ttl::Expr::new(self, false)
+    }
+    /// Wrap the client so every item level request is reported to the observer
+    fn with_observer<O: RequestObserver>(self, observer: O) -> Observed<Self, O>
+    where
+        Self: Sized,
+    {
+        Observed::new(self, observer)
+    }
+    /// Wrap the client so every item level request is retried according to the policy
+    fn with_retry(self, policy: RetryPolicy) -> Retry<Self>
+    where
+        Self: Sized,
+    {
+        Retry::new(self, policy)
+    }
+    /// Wrap the client so the capacity consumed by requests to a table or index is limited.
+    /// More tables can be limited with [`RateLimited::limit`]
+    fn with_rate_limit<T: TableIndex>(self, limit: RateLimit) -> RateLimited<Self>
+    where
+        Self: Sized,
+    {
+        RateLimited::new(self).limit::<T>(limit)
+    }
 }
 
 impl<D: rusoto_dynamodb::DynamoDb> DynamoDb for D {}
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt};
 
 use rusoto_dynamodb::AttributeValue;
 
@@ -24,6 +24,142 @@ pub fn extract<T: FromAttributeValue>(map: &mut Attributes, key: &str) -> Result
     T::try_from_av(map.remove(key).ok_or_else(|| AttributeError::MissingField(key.to_owned()))?)
 }
 
+/// Type of a dynamodb attribute value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeType {
+    /// String
+    S,
+    /// Number
+    N,
+    /// Binary
+    B,
+    /// Boolean
+    Bool,
+    /// Null
+    Null,
+    /// Map
+    M,
+    /// List
+    L,
+    /// String set
+    Ss,
+    /// Number set
+    Ns,
+    /// Binary set
+    Bs,
+    /// Attribute value without any type set
+    Empty,
+}
+
+impl AttributeType {
+    /// The type of the attribute value
+    #[must_use]
+    pub const fn of(av: &AttributeValue) -> Self {
+        let AttributeValue { b, bool, bs, l, m, n, ns, null, s, ss } = av;
+        if s.is_some() {
+            Self::S
+        } else if n.is_some() {
+            Self::N
+        } else if b.is_some() {
+            Self::B
+        } else if bool.is_some() {
+            Self::Bool
+        } else if null.is_some() {
+            Self::Null
+        } else if m.is_some() {
+            Self::M
+        } else if l.is_some() {
+            Self::L
+        } else if ss.is_some() {
+            Self::Ss
+        } else if ns.is_some() {
+            Self::Ns
+        } else if bs.is_some() {
+            Self::Bs
+        } else {
+            Self::Empty
+        }
+    }
+
+    /// The dynamodb name of the type
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::S => "S",
+            Self::N => "N",
+            Self::B => "B",
+            Self::Bool => "BOOL",
+            Self::Null => "NULL",
+            Self::M => "M",
+            Self::L => "L",
+            Self::Ss => "SS",
+            Self::Ns => "NS",
+            Self::Bs => "BS",
+            Self::Empty => "empty",
+        }
+    }
+}
+
+impl fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Take the value of the expected type out of an attribute value
+///
+/// ```ignore
+/// let s: String = expect(av, AttributeType::S, |av| av.s.take())?;
+/// ```
+///
+/// # Errors
+/// Will return [`AttributeError::IncorrectType`] if `take` returns `None`
+pub fn expect<T>(mut av: AttributeValue, expected: AttributeType, take: impl FnOnce(&mut AttributeValue) -> Option<T>) -> Result<T, AttributeError> {
+    take(&mut av).ok_or_else(|| AttributeError::incorrect_type(expected, &av))
+}
+
+/// Collects the errors of every field of an item, so that they can be reported together
+#[derive(Debug, Default)]
+pub struct Errors(Vec<AttributeError>);
+
+impl Errors {
+    /// Create an empty collection
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Keep the parsed value, or record the error
+    pub fn check<T>(&mut self, result: Result<T, AttributeError>) -> Option<T> {
+        result.map_err(|err| self.0.push(err)).ok()
+    }
+
+    /// Combine the recorded errors into one, which is [`AttributeError::Multiple`] if there is more than one
+    #[must_use]
+    pub fn into_error(mut self) -> AttributeError {
+        if self.0.len() == 1 {
+            self.0.remove(0)
+        } else {
+            AttributeError::Multiple(self.0)
+        }
+    }
+}
+
+/// Create a `NULL` attribute value
+#[must_use]
+pub fn null() -> AttributeValue {
+    AttributeValue {
+        null: Some(true),
+        ..AttributeValue::default()
+    }
+}
+
+/// Check whether the attribute value is `NULL`
+#[must_use]
+pub fn is_null(av: &AttributeValue) -> bool {
+    av.null == Some(true)
+}
+
 /// Trait for types that can be created from `AttribueValues`
 pub trait FromAttributeValue: Sized {
     /// try convert the attribute value into Self
@@ -41,7 +177,7 @@ pub trait IntoAttributeValue: Sized {
 
 impl FromAttributeValue for String {
     fn try_from_av(av: AttributeValue) -> Result<Self, AttributeError> {
-        av.s.ok_or(AttributeError::IncorrectType)
+        expect(av, AttributeType::S, |av| av.s.take())
     }
 }
 
@@ -79,8 +215,8 @@ where
     T: FromAttributeValue,
 {
     fn try_from_av(av: AttributeValue) -> Result<Self, AttributeError> {
-        let l = av.l.ok_or(AttributeError::IncorrectType)?;
-        l.into_iter().map(T::try_from_av).collect()
+        let l = expect(av, AttributeType::L, |av| av.l.take())?;
+        l.into_iter().enumerate().map(|(i, av)| T::try_from_av(av).map_err(|e| e.in_index(i))).collect()
     }
 }
 
@@ -113,13 +249,7 @@ where
     T: IntoAttributeValue,
 {
     fn into_av(self) -> AttributeValue {
-        self.map_or_else(
-            || AttributeValue {
-                null: Some(true),
-                ..AttributeValue::default()
-            },
-            T::into_av,
-        )
+        self.map_or_else(null, T::into_av)
     }
 }
 
@@ -127,7 +257,7 @@ macro_rules! convert_num {
     ($n:ident) => {
         impl FromAttributeValue for $n {
             fn try_from_av(av: AttributeValue) -> Result<Self, AttributeError> {
-                let n = av.n.ok_or(AttributeError::IncorrectType)?;
+                let n = expect(av, AttributeType::N, |av| av.n.take())?;
                 match n.parse() {
                     Ok(n) => Ok(n),
                     Err(e) => Err(AttributeError::ParseError(Box::new(e))),
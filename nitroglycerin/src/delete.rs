@@ -1,8 +1,15 @@
-use std::convert::TryFrom;
+use std::{convert::TryFrom, marker::PhantomData};
 
-use rusoto_dynamodb::{DeleteItemError, DeleteItemInput};
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::{DeleteItemError, DeleteItemInput, DeleteItemOutput};
 
-use crate::{key, AttributeError, Attributes, DynamoDb, DynamoError, Table};
+use crate::{
+    key,
+    meta::{ConsumedCapacityInput, Meta, ReturnConsumedCapacity},
+    trace,
+    version::{Condition, HasVersion},
+    AttributeError, Attributes, DynamoDb, DynamoError, Table,
+};
 
 /// Trait that declares a type can be built into a delete item request
 pub trait Delete<'d, D: 'd + ?Sized>: Table {
@@ -35,12 +42,64 @@ where
     /// Execute the delete item request
     ///
     /// # Errors
-    /// Will error if the dynamodb request fails
+    /// Will error if the dynamodb request fails, or with [`DynamoError::VersionMismatch`]
+    /// if the stored version does not match the one set by [`if_version`](key::Expr::if_version)
     pub async fn execute(self) -> Result<(), DynamoError<DeleteItemError>> {
-        let Self { client, input, _phantom } = self;
-        client.delete_item(input).await?;
+        self.execute_with_meta().await?;
         Ok(())
     }
+
+    /// Execute the delete item request, returning the consumed capacity and item collection metrics
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails, or with [`DynamoError::VersionMismatch`]
+    /// if the stored version does not match the one set by [`if_version`](key::Expr::if_version)
+    pub async fn execute_with_meta(self) -> Result<Meta, DynamoError<DeleteItemError>> {
+        let Self { client, input, _phantom } = self;
+        let span = trace::span(&input);
+        trace::instrument(span, async move {
+            let mut output = client.delete_item(input).await.map_err(version_mismatch)?;
+            Ok(meta(&mut output))
+        })
+        .await
+    }
+}
+
+impl<'d, D: 'd + ?Sized, T> key::Expr<'d, D, DeleteItemInput, T> {
+    /// Report the size of the affected item collection in the [`Meta`] returned by `execute_with_meta`
+    #[must_use]
+    pub fn return_item_collection_metrics(mut self) -> Self {
+        self.input.return_item_collection_metrics = Some("SIZE".to_owned());
+        self
+    }
+}
+
+fn meta(output: &mut DeleteItemOutput) -> Meta {
+    Meta {
+        consumed_capacity: output.consumed_capacity.take(),
+        item_collection_metrics: output.item_collection_metrics.take(),
+        ..Meta::default()
+    }
+}
+
+impl<'d, D: 'd + ?Sized, T: HasVersion> key::Expr<'d, D, DeleteItemInput, T> {
+    /// Only delete the item if its stored version equals `version`
+    #[must_use]
+    pub fn if_version(mut self, version: u64) -> Self {
+        let Condition { expression, names, values } = Condition::new(T::version_attribute_name(), version);
+        self.input.condition_expression = Some(expression);
+        self.input.expression_attribute_names = Some(names);
+        self.input.expression_attribute_values = values;
+        self
+    }
+}
+
+/// delete requests are only conditional when a version is expected
+fn version_mismatch(err: RusotoError<DeleteItemError>) -> DynamoError<DeleteItemError> {
+    match err {
+        RusotoError::Service(DeleteItemError::ConditionalCheckFailed(_)) => DynamoError::VersionMismatch,
+        err => err.into(),
+    }
 }
 
 impl<'d, D: 'd + ?Sized, T> key::Expr<'d, D, DeleteItemInput, T> {
@@ -50,12 +109,12 @@ impl<'d, D: 'd + ?Sized, T> key::Expr<'d, D, DeleteItemInput, T> {
     /// Will error if the dynamodb request fails
     #[must_use]
     pub fn return_all_old(self) -> key::Expr<'d, D, ReturnAllOld, T> {
-        let Self { client, mut input, _phantom } = self;
+        let Self { client, mut input, .. } = self;
         input.return_values = Some("ALL_OLD".to_string());
         key::Expr {
             client,
             input: ReturnAllOld { input },
-            _phantom,
+            _phantom: PhantomData,
         }
     }
 }
@@ -66,6 +125,12 @@ pub struct ReturnAllOld {
     input: DeleteItemInput,
 }
 
+impl ConsumedCapacityInput for ReturnAllOld {
+    fn set_return_consumed_capacity(&mut self, return_consumed_capacity: ReturnConsumedCapacity) {
+        self.input.set_return_consumed_capacity(return_consumed_capacity);
+    }
+}
+
 impl<'d, D: 'd + ?Sized, T> key::Expr<'d, D, ReturnAllOld, T>
 where
     D: DynamoDb,
@@ -77,9 +142,23 @@ where
     /// # Errors
     /// Will error if the dynamodb request fails
     pub async fn execute(self) -> Result<T, DynamoError<DeleteItemError>> {
+        Ok(self.execute_with_meta().await?.0)
+    }
+
+    /// Execute the delete item request returning the contents of the deleted item, along with the consumed capacity
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails
+    pub async fn execute_with_meta(self) -> Result<(T, Meta), DynamoError<DeleteItemError>> {
         let Self { client, input, _phantom } = self;
-        let output = client.delete_item(input.input).await?;
-        let item = output.attributes.ok_or(AttributeError::MissingAttributes)?;
-        Ok(T::try_from(item)?)
+        let span = trace::span(&input.input);
+        let (table_name, key) = (input.input.table_name.clone(), input.input.key.clone());
+        trace::instrument(span, async move {
+            let mut output = client.delete_item(input.input).await.map_err(version_mismatch)?;
+            let meta = meta(&mut output);
+            let item = output.attributes.ok_or(AttributeError::MissingAttributes)?;
+            Ok((T::try_from(item).map_err(DynamoError::item(&table_name, key))?, meta))
+        })
+        .await
     }
 }
@@ -0,0 +1,201 @@
+use std::{error::Error, fmt};
+
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::{
+    BatchExecuteStatementError, BatchGetItemError, BatchWriteItemError, DeleteItemError, ExecuteStatementError, ExecuteTransactionError, GetItemError, PutItemError, QueryError, ScanError,
+    TransactGetItemsError, TransactWriteItemsError, UpdateItemError, UpdateTimeToLiveError,
+};
+
+use crate::{AttributeError, Attributes, DynamoError};
+
+/// Operation independent classification of a request error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The request exceeded the provisioned throughput or a request rate limit
+    Throttled,
+    /// A condition expression or item version check failed
+    ConditionFailed,
+    /// The item was being modified by a concurrent transaction
+    TransactionConflict,
+    /// The table or index does not exist
+    ResourceNotFound,
+    /// The request was rejected as invalid
+    Validation,
+    /// Dynamodb failed to process the request
+    Service,
+    /// The request could not be sent or its response could not be received
+    Network,
+    /// The response could not be parsed
+    Parse,
+}
+
+impl ErrorKind {
+    /// Short `snake_case` name of the kind
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Throttled => "throttled",
+            Self::ConditionFailed => "condition_failed",
+            Self::TransactionConflict => "transaction_conflict",
+            Self::ResourceNotFound => "resource_not_found",
+            Self::Validation => "validation",
+            Self::Service => "service",
+            Self::Network => "network",
+            Self::Parse => "parse",
+        }
+    }
+
+    /// Whether a request that failed with this kind of error may succeed if it is sent again
+    #[must_use]
+    pub const fn is_retryable(self) -> bool {
+        matches!(self, Self::Throttled | Self::TransactionConflict | Self::Service | Self::Network)
+    }
+}
+
+/// Errors that can be classified into an [`ErrorKind`]
+pub trait Classify {
+    /// The kind of the error
+    fn kind(&self) -> ErrorKind;
+
+    /// Whether the request that failed with this error may succeed if it is sent again
+    fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+}
+
+/// Transactions report their cancellation reasons in the message, e.g. `[ConditionalCheckFailed, None]`
+fn cancellation_kind(message: &str) -> ErrorKind {
+    if message.contains("ConditionalCheckFailed") {
+        ErrorKind::ConditionFailed
+    } else if message.contains("ThrottlingError") || message.contains("ProvisionedThroughputExceeded") {
+        ErrorKind::Throttled
+    } else if message.contains("ValidationError") || message.contains("ItemCollectionSizeLimitExceeded") {
+        ErrorKind::Validation
+    } else {
+        ErrorKind::TransactionConflict
+    }
+}
+
+macro_rules! classify {
+    ($($error:ident => $($variant:ident)|*;)*) => {
+        $(impl Classify for $error {
+            #[allow(unused_variables)]
+            fn kind(&self) -> ErrorKind {
+                match self {
+                    $(Self::$variant(message) => classify!(@kind $variant message),)*
+                }
+            }
+        })*
+    };
+    (@kind ConditionalCheckFailed $m:ident) => { ErrorKind::ConditionFailed };
+    (@kind DuplicateItem $m:ident) => { ErrorKind::ConditionFailed };
+    (@kind IdempotentParameterMismatch $m:ident) => { ErrorKind::Validation };
+    (@kind InternalServerError $m:ident) => { ErrorKind::Service };
+    (@kind ItemCollectionSizeLimitExceeded $m:ident) => { ErrorKind::Validation };
+    (@kind LimitExceeded $m:ident) => { ErrorKind::Throttled };
+    (@kind ProvisionedThroughputExceeded $m:ident) => { ErrorKind::Throttled };
+    (@kind RequestLimitExceeded $m:ident) => { ErrorKind::Throttled };
+    (@kind ResourceInUse $m:ident) => { ErrorKind::Validation };
+    (@kind ResourceNotFound $m:ident) => { ErrorKind::ResourceNotFound };
+    (@kind TransactionCanceled $m:ident) => { cancellation_kind($m) };
+    (@kind TransactionConflict $m:ident) => { ErrorKind::TransactionConflict };
+    (@kind TransactionInProgress $m:ident) => { ErrorKind::TransactionConflict };
+}
+
+classify! {
+    GetItemError => InternalServerError | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound;
+    PutItemError => ConditionalCheckFailed | InternalServerError | ItemCollectionSizeLimitExceeded | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound | TransactionConflict;
+    DeleteItemError => ConditionalCheckFailed | InternalServerError | ItemCollectionSizeLimitExceeded | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound | TransactionConflict;
+    UpdateItemError => ConditionalCheckFailed | InternalServerError | ItemCollectionSizeLimitExceeded | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound | TransactionConflict;
+    QueryError => InternalServerError | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound;
+    ScanError => InternalServerError | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound;
+    BatchGetItemError => InternalServerError | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound;
+    BatchWriteItemError => InternalServerError | ItemCollectionSizeLimitExceeded | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound;
+    TransactGetItemsError => InternalServerError | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound | TransactionCanceled;
+    TransactWriteItemsError => IdempotentParameterMismatch | InternalServerError | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound | TransactionCanceled | TransactionInProgress;
+    ExecuteStatementError => ConditionalCheckFailed | DuplicateItem | InternalServerError | ItemCollectionSizeLimitExceeded | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound | TransactionConflict;
+    BatchExecuteStatementError => InternalServerError | RequestLimitExceeded;
+    ExecuteTransactionError => IdempotentParameterMismatch | InternalServerError | ProvisionedThroughputExceeded | RequestLimitExceeded | ResourceNotFound | TransactionCanceled | TransactionInProgress;
+    UpdateTimeToLiveError => InternalServerError | LimitExceeded | ResourceInUse | ResourceNotFound;
+}
+
+impl<E: Classify> Classify for RusotoError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Service(err) => err.kind(),
+            Self::HttpDispatch(_) | Self::Blocking => ErrorKind::Network,
+            Self::Credentials(_) | Self::Validation(_) => ErrorKind::Validation,
+            Self::ParseError(_) => ErrorKind::Parse,
+            Self::Unknown(response) => {
+                // errors rusoto does not know the type of, such as `ThrottlingException` and `ValidationException`
+                let body = String::from_utf8_lossy(&response.body);
+                if body.contains("ThrottlingException") || body.contains("ProvisionedThroughputExceeded") || body.contains("RequestLimitExceeded") {
+                    ErrorKind::Throttled
+                } else if body.contains("ConditionalCheckFailed") {
+                    ErrorKind::ConditionFailed
+                } else if body.contains("ResourceNotFound") {
+                    ErrorKind::ResourceNotFound
+                } else if body.contains("TransactionConflict") {
+                    ErrorKind::TransactionConflict
+                } else if response.status.is_server_error() {
+                    ErrorKind::Service
+                } else {
+                    ErrorKind::Validation
+                }
+            }
+        }
+    }
+}
+
+impl<E: Error + Classify + 'static> Classify for DynamoError<E> {
+    fn kind(&self) -> ErrorKind {
+        self.kind()
+    }
+}
+
+/// Segment of an [`AttributePath`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// Named field of a map attribute
+    Field(String),
+    /// Element of a list attribute
+    Index(usize),
+}
+
+/// Path to a nested attribute, such as `orders[3].address.zip`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AttributePath(pub Vec<PathSegment>);
+
+impl fmt::Display for AttributePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if i == 0 => f.write_str(name)?,
+                PathSegment::Field(name) => write!(f, ".{name}")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formats key attributes as `id="user_1", sk=3`, sorted by name
+pub(crate) fn display_key(key: &Attributes) -> String {
+    let mut key: Vec<_> = key.iter().collect();
+    key.sort_by_key(|(name, _)| *name);
+    let key: Vec<_> = key
+        .into_iter()
+        .map(|(name, av)| match (&av.s, &av.n) {
+            (Some(s), _) => format!("{name}={s:?}"),
+            (_, Some(n)) => format!("{name}={n}"),
+            _ => format!("{name}={av:?}"),
+        })
+        .collect();
+    format!("{{{}}}", key.join(", "))
+}
+
+/// Formats parse errors as `missing field id; at n: incorrect type: expected N, found S`
+pub(crate) fn display_errors(errors: &[AttributeError]) -> String {
+    let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    errors.join("; ")
+}
@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 
 use rusoto_dynamodb::{GetItemError, GetItemInput};
 
-use crate::{AttributeError, Attributes, DynamoError, Table, client::DynamoDb, key};
+use crate::{client::DynamoDb, key, meta::Meta, trace, AttributeError, Attributes, DynamoError, Table};
 
 /// Trait that declares a type can be built into a get item request
 pub trait Get<'d, D: ?Sized>: Table {
@@ -47,8 +47,26 @@ where
     /// # Errors
     /// Will error if the dynamodb request fails or if the result could not be parsed
     pub async fn execute(self) -> Result<Option<T>, DynamoError<GetItemError>> {
+        Ok(self.execute_with_meta().await?.0)
+    }
+
+    /// Execute the get item request, also returning the consumed capacity
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails or if the result could not be parsed
+    pub async fn execute_with_meta(self) -> Result<(Option<T>, Meta), DynamoError<GetItemError>> {
         let Self { client, input, _phantom } = self;
-        let output = client.get_item(input).await?;
-        Ok(output.item.map(T::try_from).transpose()?)
+        let span = trace::span(&input);
+        let (table_name, key) = (input.table_name.clone(), input.key.clone());
+        trace::instrument(span, async move {
+            let output = client.get_item(input).await?;
+            let meta = Meta {
+                consumed_capacity: output.consumed_capacity,
+                ..Meta::default()
+            };
+            let item = output.item.map(T::try_from).transpose().map_err(DynamoError::item(&table_name, key))?;
+            Ok((item, meta))
+        })
+        .await
     }
 }
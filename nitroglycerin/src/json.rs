@@ -0,0 +1,367 @@
+use rusoto_dynamodb::AttributeValue;
+use serde_json::{Map, Value};
+
+use crate::{AttributeError, Attributes};
+
+/// Convert an attribute value to dynamodb json, such as `{"S": "foo"}` or `{"L": [{"N": "1"}]}`
+///
+/// Binary values are base64 encoded, and numbers are kept as strings so that no precision is lost
+#[must_use]
+pub fn to_value(av: &AttributeValue) -> Value {
+    let AttributeValue { b, bool, bs, l, m, n, ns, null, s, ss } = av;
+    let (key, value) = if let Some(s) = s {
+        ("S", Value::from(s.as_str()))
+    } else if let Some(n) = n {
+        ("N", Value::from(n.as_str()))
+    } else if let Some(b) = b {
+        ("B", Value::from(base64::encode(b)))
+    } else if let Some(bool) = bool {
+        ("BOOL", Value::from(*bool))
+    } else if let Some(null) = null {
+        ("NULL", Value::from(*null))
+    } else if let Some(m) = m {
+        ("M", item_to_value(m))
+    } else if let Some(l) = l {
+        ("L", l.iter().map(to_value).collect())
+    } else if let Some(ss) = ss {
+        ("SS", ss.iter().map(String::as_str).collect())
+    } else if let Some(ns) = ns {
+        ("NS", ns.iter().map(String::as_str).collect())
+    } else if let Some(bs) = bs {
+        ("BS", bs.iter().map(base64::encode).collect())
+    } else {
+        return Value::Object(Map::new());
+    };
+    let mut object = Map::new();
+    object.insert(key.to_owned(), value);
+    Value::Object(object)
+}
+
+/// Convert an item to a dynamodb json object, with one typed value per attribute
+#[must_use]
+pub fn item_to_value(item: &Attributes) -> Value {
+    item.iter().map(|(name, av)| (name.clone(), to_value(av))).collect::<Map<_, _>>().into()
+}
+
+/// Format an item as a dynamodb json string
+#[must_use]
+pub fn to_string(item: &Attributes) -> String {
+    item_to_value(item).to_string()
+}
+
+/// Format an item as an indented dynamodb json string
+#[must_use]
+pub fn to_string_pretty(item: &Attributes) -> String {
+    format!("{:#}", item_to_value(item))
+}
+
+fn invalid(message: String) -> AttributeError {
+    AttributeError::ParseError(message.into())
+}
+
+fn string(value: Value) -> Result<String, AttributeError> {
+    match value {
+        Value::String(s) => Ok(s),
+        value => Err(invalid(format!("expected a string, found {value}"))),
+    }
+}
+
+/// Numbers are strings on the wire, but hand written json often uses plain numbers
+fn number(value: Value) -> Result<String, AttributeError> {
+    match value {
+        Value::Number(n) => Ok(n.to_string()),
+        value => string(value),
+    }
+}
+
+fn binary(value: Value) -> Result<Vec<u8>, AttributeError> {
+    base64::decode(string(value)?).map_err(|err| AttributeError::ParseError(Box::new(err)))
+}
+
+fn boolean(value: Value) -> Result<bool, AttributeError> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        value => Err(invalid(format!("expected a bool, found {value}"))),
+    }
+}
+
+fn list<T>(value: Value, f: impl Fn(Value) -> Result<T, AttributeError>) -> Result<Vec<T>, AttributeError> {
+    match value {
+        Value::Array(values) => values.into_iter().enumerate().map(|(i, value)| f(value).map_err(|e| e.in_index(i))).collect(),
+        value => Err(invalid(format!("expected an array, found {value}"))),
+    }
+}
+
+/// Parse an attribute value from dynamodb json, such as `{"S": "foo"}`
+///
+/// # Errors
+/// Will error if the value is not an object with exactly one known type key, or its contents do not match that type
+pub fn from_value(value: Value) -> Result<AttributeValue, AttributeError> {
+    match value {
+        Value::Object(object) if object.len() == 1 => {
+            let mut av = AttributeValue::default();
+            for (key, value) in object {
+                set(&mut av, &key, value)?;
+            }
+            Ok(av)
+        }
+        value => Err(invalid(format!("expected an object with exactly one type key, found {value}"))),
+    }
+}
+
+fn set(av: &mut AttributeValue, key: &str, value: Value) -> Result<(), AttributeError> {
+    match key {
+        "S" => av.s = Some(string(value)?),
+        "N" => av.n = Some(number(value)?),
+        "B" => av.b = Some(binary(value)?.into()),
+        "BOOL" => av.bool = Some(boolean(value)?),
+        "NULL" => av.null = Some(boolean(value)?),
+        "M" => av.m = Some(item_from_value(value)?),
+        "L" => av.l = Some(list(value, from_value)?),
+        "SS" => av.ss = Some(list(value, string)?),
+        "NS" => av.ns = Some(list(value, number)?),
+        "BS" => av.bs = Some(list(value, |value| Ok(binary(value)?.into()))?),
+        key => return Err(invalid(format!("unknown attribute type {key:?}"))),
+    }
+    Ok(())
+}
+
+/// Parse an item from a dynamodb json object
+///
+/// # Errors
+/// Will error if the value is not an object, or one of its attributes is not valid dynamodb json
+pub fn item_from_value(value: Value) -> Result<Attributes, AttributeError> {
+    match value {
+        Value::Object(object) => object.into_iter().map(|(name, value)| from_value(value).map_err(|e| e.in_field(&name)).map(|av| (name, av))).collect(),
+        value => Err(invalid(format!("expected an object, found {value}"))),
+    }
+}
+
+/// Parse an item from a dynamodb json string
+///
+/// # Errors
+/// Will error if the string is not json, or not a valid dynamodb json item
+pub fn from_str(s: &str) -> Result<Attributes, AttributeError> {
+    let value = serde_json::from_str(s).map_err(|err| AttributeError::ParseError(Box::new(err)))?;
+    item_from_value(value)
+}
+
+/// How sets are read back from plain json arrays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sets {
+    /// Arrays are always read as lists
+    List,
+    /// Non empty arrays of distinct strings or of numbers are read as string or number sets
+    Infer,
+}
+
+/// How binary values are written as plain json
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binary {
+    /// A base64 string
+    Base64,
+    /// An array of byte values
+    Array,
+}
+
+/// How numbers that an `f64` cannot represent exactly are written as plain json
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BigNumbers {
+    /// Keep the exact value as a string
+    String,
+    /// Round to the nearest `f64`
+    Lossy,
+}
+
+/// How strings holding a number are read back from plain json, such as those written by [`BigNumbers::String`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericStrings {
+    /// Read them as strings
+    String,
+    /// Read them as numbers. This also turns strings such as zip codes into numbers
+    Number,
+}
+
+/// Options for converting items to and from plain json, such as `{"id": "x", "joined": 1}`
+///
+/// Plain json has no type information, so the conversion is lossy: sets become arrays,
+/// binary values become strings or arrays, and big numbers may become strings.
+/// Reading them back yields lists, strings and numbers unless configured otherwise.
+/// Plain json numbers are parsed by `serde_json`, which rounds them to an `f64` unless the `json-exact-numbers`
+/// feature is enabled. With it, they are read exactly as written, however many digits they have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlainJson {
+    sets: Sets,
+    binary: Binary,
+    big_numbers: BigNumbers,
+    numeric_strings: NumericStrings,
+}
+
+impl Default for PlainJson {
+    fn default() -> Self {
+        Self {
+            sets: Sets::List,
+            binary: Binary::Base64,
+            big_numbers: BigNumbers::String,
+            numeric_strings: NumericStrings::String,
+        }
+    }
+}
+
+impl PlainJson {
+    /// How sets are read back from arrays
+    #[must_use]
+    pub const fn sets(mut self, sets: Sets) -> Self {
+        self.sets = sets;
+        self
+    }
+
+    /// How binary values are written
+    #[must_use]
+    pub const fn binary(mut self, binary: Binary) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    /// How numbers that an `f64` cannot represent exactly are written
+    #[must_use]
+    pub const fn big_numbers(mut self, big_numbers: BigNumbers) -> Self {
+        self.big_numbers = big_numbers;
+        self
+    }
+
+    /// How strings holding a number are read back
+    #[must_use]
+    pub const fn numeric_strings(mut self, numeric_strings: NumericStrings) -> Self {
+        self.numeric_strings = numeric_strings;
+        self
+    }
+
+    fn number(self, n: &str) -> Value {
+        let exact = n.parse::<i64>().is_ok() || n.parse::<u64>().is_ok() || significant_digits(n) <= 15;
+        match n.parse::<serde_json::Number>() {
+            Ok(number) if exact => Value::Number(number),
+            _ if self.big_numbers == BigNumbers::Lossy => n.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map_or_else(|| Value::from(n), Value::Number),
+            _ => Value::from(n),
+        }
+    }
+
+    fn binary_value(self, b: &[u8]) -> Value {
+        match self.binary {
+            Binary::Base64 => Value::from(base64::encode(b)),
+            Binary::Array => b.iter().copied().map(Value::from).collect(),
+        }
+    }
+
+    /// Convert an attribute value to plain json
+    #[must_use]
+    pub fn to_value(self, av: &AttributeValue) -> Value {
+        match av {
+            AttributeValue { s: Some(s), .. } => Value::from(s.as_str()),
+            AttributeValue { n: Some(n), .. } => self.number(n),
+            AttributeValue { b: Some(b), .. } => self.binary_value(b),
+            AttributeValue { bool: Some(bool), .. } => Value::from(*bool),
+            AttributeValue { m: Some(m), .. } => self.item_to_value(m),
+            AttributeValue { l: Some(l), .. } => l.iter().map(|av| self.to_value(av)).collect(),
+            AttributeValue { ss: Some(ss), .. } => ss.iter().map(String::as_str).collect(),
+            AttributeValue { ns: Some(ns), .. } => ns.iter().map(|n| self.number(n)).collect(),
+            AttributeValue { bs: Some(bs), .. } => bs.iter().map(|b| self.binary_value(b)).collect(),
+            _ => Value::Null,
+        }
+    }
+
+    /// Convert an item to a plain json object
+    #[must_use]
+    pub fn item_to_value(self, item: &Attributes) -> Value {
+        item.iter().map(|(name, av)| (name.clone(), self.to_value(av))).collect::<Map<_, _>>().into()
+    }
+
+    /// Parse an attribute value from plain json
+    #[must_use]
+    pub fn from_value(self, value: Value) -> AttributeValue {
+        let mut av = AttributeValue::default();
+        match value {
+            Value::Null => av.null = Some(true),
+            Value::Bool(b) => av.bool = Some(b),
+            Value::Number(n) => av.n = Some(n.to_string()),
+            Value::String(s) if self.numeric_strings == NumericStrings::Number && is_numeric(&s) => av.n = Some(s),
+            Value::String(s) => av.s = Some(s),
+            Value::Object(object) => av.m = Some(object.into_iter().map(|(name, value)| (name, self.from_value(value))).collect()),
+            Value::Array(values) => match self.sets {
+                Sets::Infer if self.is_number_set(&values) => av.ns = Some(values.iter().filter_map(number_text).collect()),
+                Sets::Infer if is_string_set(&values) => av.ss = Some(values.into_iter().filter_map(|v| v.as_str().map(ToOwned::to_owned)).collect()),
+                _ => av.l = Some(values.into_iter().map(|value| self.from_value(value)).collect()),
+            },
+        }
+        av
+    }
+
+    fn is_number(self, value: &Value) -> bool {
+        match value {
+            Value::Number(_) => true,
+            Value::String(s) => self.numeric_strings == NumericStrings::Number && is_numeric(s),
+            _ => false,
+        }
+    }
+
+    fn is_number_set(self, values: &[Value]) -> bool {
+        !values.is_empty() && values.iter().all(|value| self.is_number(value))
+    }
+
+    /// Parse an item from a plain json object
+    ///
+    /// # Errors
+    /// Will error if the value is not an object
+    pub fn item_from_value(self, value: Value) -> Result<Attributes, AttributeError> {
+        match value {
+            Value::Object(object) => Ok(object.into_iter().map(|(name, value)| (name, self.from_value(value))).collect()),
+            value => Err(invalid(format!("expected an object, found {value}"))),
+        }
+    }
+}
+
+/// Number of significant decimal digits, ignoring leading and trailing zeros
+fn significant_digits(n: &str) -> usize {
+    let mantissa = n.split(['e', 'E']).next().unwrap_or_default();
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    digits.trim_start_matches('0').trim_end_matches('0').len()
+}
+
+/// Whether the string has the syntax of a dynamodb number, such as `-12.5e3`
+fn is_numeric(s: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let (mantissa, exponent) = s.split_once(['e', 'E']).unwrap_or((s, "0"));
+    let mantissa = mantissa.strip_prefix('-').unwrap_or(mantissa);
+    let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+    let (int, fraction) = mantissa.split_once('.').unwrap_or((mantissa, "0"));
+    digits(int) && digits(fraction) && digits(exponent)
+}
+
+/// The text of a json number, or of a string holding one
+fn number_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn is_string_set(values: &[Value]) -> bool {
+    let strings: std::collections::HashSet<_> = values.iter().filter_map(Value::as_str).collect();
+    !values.is_empty() && strings.len() == values.len()
+}
+
+
+/// Convert an item to plain json with the default [`PlainJson`] options
+#[must_use]
+pub fn to_plain_json(item: &Attributes) -> Value {
+    PlainJson::default().item_to_value(item)
+}
+
+/// Parse an item from plain json with the default [`PlainJson`] options
+///
+/// # Errors
+/// Will error if the value is not an object
+pub fn from_plain_json(value: Value) -> Result<Attributes, AttributeError> {
+    PlainJson::default().item_from_value(value)
+}
@@ -1,6 +1,10 @@
 use std::marker::PhantomData;
 
-use crate::{convert::IntoAttributeValue, Attributes, Table};
+use crate::{
+    convert::IntoAttributeValue,
+    meta::{ConsumedCapacityInput, ReturnConsumedCapacity},
+    Attributes, Table,
+};
 
 /// Key type that can be built into `GetItem` requests
 pub struct Key {
@@ -23,6 +27,51 @@ impl Key {
     }
 }
 
+/// Join the components of a composite key value, such as `USER#{user_id}`
+///
+/// `literals` holds the text surrounding each component, so it must have one more entry than `parts`.
+/// Every component but the first must not contain the literal text that comes before it,
+/// otherwise [`decompose`] will not split the value back into the same components
+#[must_use]
+pub fn compose(literals: &[&str], parts: &[String]) -> String {
+    let mut value = String::from(literals[0]);
+    for (part, literal) in parts.iter().zip(&literals[1..]) {
+        value.push_str(part);
+        value.push_str(literal);
+    }
+    value
+}
+
+/// Split a composite key value into its components. The inverse of [`compose`]
+///
+/// The value is split from the right, so only the first component may contain the separators
+///
+/// Returns `None` if the value does not match the literal text of the template
+#[must_use]
+pub fn decompose<'v>(value: &'v str, literals: &[&str]) -> Option<Vec<&'v str>> {
+    let (last, separators) = literals[1..].split_last()?;
+    let mut rest = value.strip_prefix(literals[0])?.strip_suffix(last)?;
+
+    let mut parts = Vec::with_capacity(literals.len() - 1);
+    for separator in separators.iter().rev() {
+        let (head, part) = rest.rsplit_once(separator)?;
+        parts.push(part);
+        rest = head;
+    }
+    parts.push(rest);
+    parts.reverse();
+    Some(parts)
+}
+
+/// Trait that declares the primary key attributes of a table. Implemented by the `Key` derive
+pub trait PrimaryKey {
+    /// Name of the partition key attribute
+    fn partition_key_name() -> &'static str;
+
+    /// Name of the sort key attribute, if the table has one
+    fn sort_key_name() -> Option<&'static str>;
+}
+
 /// Trait that declares a type can be built into a request key
 pub trait Builder<'d, D: 'd + ?Sized, R: From<Key>>: Table {
     /// The builder type that performs the get item request
@@ -49,3 +98,12 @@ where
         Self { client, input, _phantom: PhantomData }
     }
 }
+
+impl<'d, D: 'd + ?Sized, Input: ConsumedCapacityInput, Table> Expr<'d, D, Input, Table> {
+    /// Report the capacity consumed by the request in the [`Meta`](crate::meta::Meta) returned by `execute_with_meta`
+    #[must_use]
+    pub fn return_consumed_capacity(mut self, return_consumed_capacity: ReturnConsumedCapacity) -> Self {
+        self.input.set_return_consumed_capacity(return_consumed_capacity);
+        self
+    }
+}
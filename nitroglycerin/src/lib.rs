@@ -63,6 +63,8 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
 #![warn(missing_docs)]
+// request bounds are kept in where clauses, next to the `Send` bounds they go with
+#![allow(clippy::type_repetition_in_bounds)]
 
 mod client;
 
@@ -78,10 +80,41 @@ pub mod put;
 pub mod delete;
 /// collection of functions and types used to make query requests
 pub mod query;
+/// collection of functions and types used to make update item requests
+pub mod update;
+/// consumed capacity and other metadata returned by requests
+pub mod meta;
+/// optimistic locking of versioned items
+pub mod version;
+/// automatic audit timestamps
+pub mod timestamp;
+/// time to live attributes, stored as seconds since the unix epoch
+pub mod ttl;
+/// kinds of item level requests and what they report
+pub mod operation;
+/// observing every request made through a client
+pub mod observe;
+/// spans for every executed request, under the `tracing` feature
+mod trace;
+/// retrying throttled and transient request failures
+pub mod retry;
+/// client side rate limiting of consumed capacity
+pub mod limit;
+/// conversion between attribute values and the dynamodb json wire format
+pub mod json;
+/// exporting tables to, and importing them from, json lines
+pub mod transfer;
+/// decoding dynamodb stream records into typed changes, under the `streams` feature
+#[cfg(feature = "streams")]
+pub mod streams;
+/// operation independent classification of request errors
+pub mod error;
 
 use std::{collections::HashMap, error::Error};
 
 pub use client::DynamoDb;
+use convert::AttributeType;
+use error::{AttributePath, Classify, ErrorKind, PathSegment};
 pub use nitroglycerin_derive::{Attributes, Key, Query};
 pub use rusoto_dynamodb as dynamodb;
 use thiserror::Error;
@@ -114,6 +147,50 @@ pub enum DynamoError<E: Error + 'static> {
     /// Error originated from a dynamodb request error
     #[error("could not connect to dynamo: {0}")]
     Rusoto(#[from] rusoto_core::RusotoError<E>),
+    /// Error occurs when a versioned item was written by someone else since it was read
+    #[error("item version does not match the stored version")]
+    VersionMismatch,
+    /// Error occurs when a versioned item is at the largest version its type can hold
+    #[error("item version cannot be incremented any further")]
+    VersionOverflow,
+    /// Error occurs when an item returned by a request could not be parsed
+    #[error("could not parse item {} in table {table_name}: {source}", error::display_key(.key))]
+    ItemParseError {
+        /// The table the item was read from
+        table_name: String,
+        /// The key attributes of the item, as far as they are known
+        key: Attributes,
+        /// The parse error
+        source: AttributeError,
+    },
+}
+
+impl<E: Error + Classify + 'static> DynamoError<E> {
+    /// Classify the error independently of the operation that returned it
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::ParseError(_) | Self::ItemParseError { .. } => ErrorKind::Parse,
+            Self::Rusoto(err) => err.kind(),
+            Self::VersionMismatch => ErrorKind::ConditionFailed,
+            Self::VersionOverflow => ErrorKind::Validation,
+        }
+    }
+
+    /// Whether the request may succeed if it is sent again
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+}
+
+impl<E: Error + 'static> DynamoError<E> {
+    /// Add the table name and key of the item being parsed to a parse error
+    pub(crate) fn item(table_name: &str, key: Attributes) -> impl FnOnce(AttributeError) -> Self + '_ {
+        move |source| Self::ItemParseError {
+            table_name: table_name.to_owned(),
+            key,
+            source,
+        }
+    }
 }
 
 /// Convenient type for a attribute value map
@@ -127,8 +204,22 @@ pub enum AttributeError {
     MissingField(String),
 
     /// Error occured because the attribute value type was not supported
-    #[error("incorrect type")]
-    IncorrectType,
+    #[error("incorrect type: expected {expected}, found {found}")]
+    IncorrectType {
+        /// The type the value should have had
+        expected: AttributeType,
+        /// The type the value had
+        found: AttributeType,
+    },
+
+    /// Error occured because a list did not have the expected number of elements
+    #[error("incorrect length: expected {expected}, found {found}")]
+    IncorrectLength {
+        /// The number of elements the list should have had
+        expected: usize,
+        /// The number of elements the list had
+        found: usize,
+    },
 
     /// Error occured because value could not be parsed
     #[error("could not parse value: {0}")]
@@ -137,4 +228,84 @@ pub enum AttributeError {
     /// Error occurs when no item is returned by dynamodb
     #[error("no item returned by dynamodb")]
     MissingAttributes,
+
+    /// Error occurs when the discriminator of an item does not match any known entity type
+    #[error("unknown entity type {0}")]
+    UnknownType(String),
+
+    /// Error occured within a nested attribute
+    #[error("at {path}: {source}")]
+    Nested {
+        /// Path to the attribute the error occured at
+        path: AttributePath,
+        /// The error
+        source: Box<Self>,
+    },
+
+    /// Error occured in more than one field of an item
+    #[error("{} fields could not be parsed: {}", .0.len(), error::display_errors(.0))]
+    Multiple(Vec<Self>),
+}
+
+impl AttributeError {
+    /// Error for a value that does not have the expected type
+    #[must_use]
+    pub const fn incorrect_type(expected: AttributeType, found: &rusoto_dynamodb::AttributeValue) -> Self {
+        Self::IncorrectType {
+            expected,
+            found: AttributeType::of(found),
+        }
+    }
+
+    /// Record that the error occured within the named field
+    #[must_use]
+    pub fn in_field(self, name: &str) -> Self {
+        self.nested(PathSegment::Field(name.to_owned()))
+    }
+
+    /// Record that the error occured within the list element at the index
+    #[must_use]
+    pub fn in_index(self, index: usize) -> Self {
+        self.nested(PathSegment::Index(index))
+    }
+
+    fn nested(self, segment: PathSegment) -> Self {
+        match self {
+            Self::Nested { mut path, source } => {
+                path.0.insert(0, segment);
+                Self::Nested { path, source }
+            }
+            source => Self::Nested {
+                path: AttributePath(vec![segment]),
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// Path to the attribute the error occured at, if it occured within a nested attribute
+    #[must_use]
+    pub const fn path(&self) -> Option<&AttributePath> {
+        match self {
+            Self::Nested { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Every error that occured, which is more than one if this is [`AttributeError::Multiple`]
+    #[must_use]
+    pub fn errors(&self) -> &[Self] {
+        match self {
+            Self::Multiple(errors) => errors,
+            err => std::slice::from_ref(err),
+        }
+    }
+
+    /// The error without the path it occured at
+    #[must_use]
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Nested { source, .. } => source,
+            err => err,
+        }
+    }
 }
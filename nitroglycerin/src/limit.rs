@@ -0,0 +1,194 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    future::Future,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::ConsumedCapacity;
+
+use crate::{
+    meta::{ConsumedCapacityInput, ReturnConsumedCapacity},
+    operation::{impl_dynamodb, Request, Response},
+    Table, TableIndex,
+};
+
+/// Capacity units per second a table or index may consume through a [`RateLimited`] client
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateLimit {
+    read: Option<f64>,
+    write: Option<f64>,
+}
+
+impl RateLimit {
+    /// Limit the read capacity units consumed per second
+    #[must_use]
+    pub const fn read(mut self, units: f64) -> Self {
+        self.read = Some(units);
+        self
+    }
+
+    /// Limit the write capacity units consumed per second
+    #[must_use]
+    pub const fn write(mut self, units: f64) -> Self {
+        self.write = Some(units);
+        self
+    }
+}
+
+/// Token bucket holding at most one second worth of capacity units
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(rate: f64) -> Mutex<Self> {
+        Mutex::new(Self {
+            rate,
+            tokens: rate,
+            updated: Instant::now(),
+        })
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = elapsed.mul_add(self.rate, self.tokens).min(self.rate);
+        self.updated = now;
+    }
+
+    /// How long until the bucket is out of debt
+    fn wait(&mut self) -> Duration {
+        self.refill();
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+
+    fn consume(&mut self, units: f64) {
+        self.refill();
+        self.tokens -= units;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    read: Option<Mutex<Bucket>>,
+    write: Option<Mutex<Bucket>>,
+}
+
+impl Buckets {
+    const fn get(&self, write: bool) -> Option<&Mutex<Bucket>> {
+        if write {
+            self.write.as_ref()
+        } else {
+            self.read.as_ref()
+        }
+    }
+}
+
+/// Dynamodb client wrapper that throttles item level requests to the tables and indexes it has a [`RateLimit`] for
+///
+/// Requests to a limited table or index wait until its bucket is no longer in debt. Every request is sent
+/// with `ReturnConsumedCapacity` set to `INDEXES` so that the capacity it consumed can be taken from the buckets
+/// of the tables and indexes it used. Requests that target more than one table are not delayed, but the
+/// capacity they consume is still counted.
+#[derive(Debug)]
+pub struct RateLimited<D> {
+    client: D,
+    buckets: HashMap<(String, Option<String>), Buckets>,
+}
+
+impl<D> RateLimited<D> {
+    /// Wrap the client, without limiting any tables yet
+    pub fn new(client: D) -> Self {
+        Self { client, buckets: HashMap::new() }
+    }
+
+    /// Limit the capacity consumed by requests to the table or index
+    #[must_use]
+    pub fn limit<T: TableIndex>(self, limit: RateLimit) -> Self {
+        self.limit_by_name(<T::Table as Table>::table_name(), T::index_name(), limit)
+    }
+
+    /// Limit the capacity consumed by requests to the named table, or to one of its indexes
+    #[must_use]
+    pub fn limit_by_name(mut self, table_name: String, index_name: Option<String>, limit: RateLimit) -> Self {
+        let buckets = Buckets {
+            read: limit.read.map(Bucket::new),
+            write: limit.write.map(Bucket::new),
+        };
+        self.buckets.insert((table_name, index_name), buckets);
+        self
+    }
+
+    /// The wrapped client
+    pub const fn client(&self) -> &D {
+        &self.client
+    }
+
+    /// Unwrap the client
+    pub fn into_inner(self) -> D {
+        self.client
+    }
+
+    fn bucket(&self, table_name: &str, index_name: Option<&str>, write: bool) -> Option<&Mutex<Bucket>> {
+        let key = (table_name.to_owned(), index_name.map(ToOwned::to_owned));
+        self.buckets.get(&key)?.get(write)
+    }
+
+    fn consume(&self, table_name: &str, index_name: Option<&str>, write: bool, units: Option<f64>) {
+        if let (Some(bucket), Some(units)) = (self.bucket(table_name, index_name, write), units) {
+            bucket.lock().unwrap_or_else(PoisonError::into_inner).consume(units);
+        }
+    }
+
+    fn charge(&self, write: bool, consumed_capacity: &[ConsumedCapacity]) {
+        for consumed in consumed_capacity {
+            let Some(table_name) = &consumed.table_name else { continue };
+            let table_units = consumed.table.as_ref().and_then(|c| c.capacity_units).or(consumed.capacity_units);
+            self.consume(table_name, None, write, table_units);
+
+            let indexes = consumed.global_secondary_indexes.iter().chain(&consumed.local_secondary_indexes).flatten();
+            for (index_name, capacity) in indexes {
+                self.consume(table_name, Some(index_name), write, capacity.capacity_units);
+            }
+        }
+    }
+}
+
+impl<D: Sync> RateLimited<D> {
+    async fn throttle<I, R, E, F, Fut>(&self, mut input: I, f: F) -> Result<R, RusotoError<E>>
+    where
+        I: Request + ConsumedCapacityInput + Send,
+        R: Response + Send,
+        E: Error + Send + 'static,
+        F: FnOnce(I) -> Fut + Send,
+        Fut: Future<Output = Result<R, RusotoError<E>>> + Send,
+    {
+        let write = I::OPERATION.is_write();
+        if let Some(bucket) = input.table_name().and_then(|table_name| self.bucket(table_name, input.index_name(), write)) {
+            loop {
+                let wait = bucket.lock().unwrap_or_else(PoisonError::into_inner).wait();
+                if wait.is_zero() {
+                    break;
+                }
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        input.set_return_consumed_capacity(ReturnConsumedCapacity::Indexes);
+        let output = f(input).await?;
+        self.charge(write, output.consumed_capacity());
+        Ok(output)
+    }
+}
+
+impl_dynamodb!(impl<D> for RateLimited<D> where [D: rusoto_dynamodb::DynamoDb + Send + Sync] via throttle);
@@ -0,0 +1,92 @@
+use rusoto_dynamodb::{
+    BatchExecuteStatementInput, BatchGetItemInput, BatchWriteItemInput, ConsumedCapacity, DeleteItemInput, ExecuteStatementInput, ExecuteTransactionInput, GetItemInput, ItemCollectionMetrics,
+    PutItemInput, QueryInput, ScanInput, TransactGetItemsInput, TransactWriteItemsInput, UpdateItemInput,
+};
+
+/// How much consumed capacity dynamodb should report for a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReturnConsumedCapacity {
+    /// Report the total consumed capacity, along with the capacity consumed by each table and index
+    Indexes,
+    /// Report only the total consumed capacity
+    Total,
+    /// Do not report consumed capacity
+    None,
+}
+
+impl ReturnConsumedCapacity {
+    /// The value of the `ReturnConsumedCapacity` request parameter
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Indexes => "INDEXES",
+            Self::Total => "TOTAL",
+            Self::None => "NONE",
+        }
+    }
+}
+
+/// Request inputs that can report the capacity they consume
+pub trait ConsumedCapacityInput {
+    /// Set the `ReturnConsumedCapacity` request parameter
+    fn set_return_consumed_capacity(&mut self, return_consumed_capacity: ReturnConsumedCapacity);
+}
+
+macro_rules! consumed_capacity_input {
+    ($($input:ident,)*) => {
+        $(impl ConsumedCapacityInput for $input {
+            fn set_return_consumed_capacity(&mut self, return_consumed_capacity: ReturnConsumedCapacity) {
+                self.return_consumed_capacity = Some(return_consumed_capacity.as_str().to_owned());
+            }
+        })*
+    };
+}
+
+consumed_capacity_input! {
+    GetItemInput,
+    PutItemInput,
+    DeleteItemInput,
+    UpdateItemInput,
+    QueryInput,
+    ScanInput,
+    BatchGetItemInput,
+    BatchWriteItemInput,
+    TransactGetItemsInput,
+    TransactWriteItemsInput,
+}
+
+// PartiQL requests cannot report the capacity they consume
+macro_rules! no_consumed_capacity_input {
+    ($($input:ident,)*) => {
+        $(impl ConsumedCapacityInput for $input {
+            fn set_return_consumed_capacity(&mut self, _: ReturnConsumedCapacity) {}
+        })*
+    };
+}
+
+no_consumed_capacity_input! {
+    ExecuteStatementInput,
+    BatchExecuteStatementInput,
+    ExecuteTransactionInput,
+}
+
+/// Metadata dynamodb returns alongside the result of a request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Meta {
+    /// Capacity consumed by the request, if it was requested with `return_consumed_capacity`
+    pub consumed_capacity: Option<ConsumedCapacity>,
+    /// Size of the item collection affected by a write request, if it was requested with `return_item_collection_metrics`
+    pub item_collection_metrics: Option<ItemCollectionMetrics>,
+    /// Number of items returned by a query request
+    pub count: Option<i64>,
+    /// Number of items evaluated by a query request before any filter was applied
+    pub scanned_count: Option<i64>,
+}
+
+impl Meta {
+    /// Total capacity units consumed by the request, or zero if consumed capacity was not requested
+    #[must_use]
+    pub fn capacity_units(&self) -> f64 {
+        self.consumed_capacity.as_ref().and_then(|c| c.capacity_units).unwrap_or_default()
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    error::Error,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::ConsumedCapacity;
+
+use crate::operation::{impl_dynamodb, Operation, Request, Response};
+
+/// Description of an item level request, passed to a [`RequestObserver`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestInfo {
+    /// The operation being performed
+    pub operation: Operation,
+    /// The table the request targets, if it targets exactly one
+    pub table_name: Option<String>,
+    /// The index the request reads from, if any
+    pub index_name: Option<String>,
+}
+
+/// Result of an item level request, passed to a [`RequestObserver`]
+#[derive(Debug, Clone, Copy)]
+pub struct Outcome<'a> {
+    /// Time taken by the request
+    pub latency: Duration,
+    /// Capacity consumed by the request, if it was requested with `return_consumed_capacity`
+    pub consumed_capacity: &'a [ConsumedCapacity],
+    /// Number of items returned by the request
+    pub count: Option<i64>,
+    /// Number of items evaluated by the request before any filter was applied
+    pub scanned_count: Option<i64>,
+    /// The error the request failed with, if any
+    pub error: Option<&'a (dyn Error + 'static)>,
+}
+
+/// Hook invoked around every item level request made through an [`Observed`] client
+pub trait RequestObserver: Send + Sync {
+    /// Called before the request is sent
+    fn before(&self, _request: &RequestInfo) {}
+    /// Called once the request has completed or failed
+    fn after(&self, _request: &RequestInfo, _outcome: &Outcome<'_>) {}
+}
+
+impl<O: RequestObserver + ?Sized> RequestObserver for Arc<O> {
+    fn before(&self, request: &RequestInfo) {
+        (**self).before(request);
+    }
+    fn after(&self, request: &RequestInfo, outcome: &Outcome<'_>) {
+        (**self).after(request, outcome);
+    }
+}
+
+/// Dynamodb client wrapper that reports every item level request to a [`RequestObserver`]
+#[derive(Debug, Clone)]
+pub struct Observed<D, O> {
+    client: D,
+    observer: O,
+}
+
+impl<D, O> Observed<D, O> {
+    /// Wrap the client so its requests are reported to the observer
+    pub const fn new(client: D, observer: O) -> Self {
+        Self { client, observer }
+    }
+
+    /// The wrapped client
+    pub const fn client(&self) -> &D {
+        &self.client
+    }
+
+    /// The observer requests are reported to
+    pub const fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Unwrap the client and observer
+    pub fn into_parts(self) -> (D, O) {
+        (self.client, self.observer)
+    }
+}
+
+impl<D: Sync, O: RequestObserver> Observed<D, O> {
+    async fn observe<I, R, E, F, Fut>(&self, input: I, f: F) -> Result<R, RusotoError<E>>
+    where
+        I: Request + Send,
+        R: Response + Send,
+        E: Error + Send + 'static,
+        F: FnOnce(I) -> Fut + Send,
+        Fut: Future<Output = Result<R, RusotoError<E>>> + Send,
+    {
+        let request = RequestInfo {
+            operation: I::OPERATION,
+            table_name: input.table_name().map(ToOwned::to_owned),
+            index_name: input.index_name().map(ToOwned::to_owned),
+        };
+        self.observer.before(&request);
+
+        let start = Instant::now();
+        let result = f(input).await;
+        let latency = start.elapsed();
+
+        let outcome = match &result {
+            Ok(output) => Outcome {
+                latency,
+                consumed_capacity: output.consumed_capacity(),
+                count: output.count(),
+                scanned_count: output.scanned_count(),
+                error: None,
+            },
+            Err(err) => Outcome {
+                latency,
+                consumed_capacity: &[],
+                count: None,
+                scanned_count: None,
+                error: Some(err),
+            },
+        };
+        self.observer.after(&request, &outcome);
+
+        result
+    }
+}
+
+impl_dynamodb!(impl<D, O> for Observed<D, O> where [D: rusoto_dynamodb::DynamoDb + Send + Sync, O: RequestObserver] via observe);
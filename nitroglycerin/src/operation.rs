@@ -0,0 +1,465 @@
+use std::{collections::HashMap, convert::TryFrom};
+
+use rusoto_dynamodb::{
+    BatchExecuteStatementInput, BatchExecuteStatementOutput, BatchGetItemInput, BatchGetItemOutput, BatchWriteItemInput, BatchWriteItemOutput, ConsumedCapacity, DeleteItemInput,
+    DeleteItemOutput, ExecuteStatementInput, ExecuteStatementOutput, ExecuteTransactionInput, ExecuteTransactionOutput, GetItemInput, GetItemOutput, PutItemInput, PutItemOutput, QueryInput,
+    QueryOutput, ScanInput, ScanOutput, TransactGetItemsInput, TransactGetItemsOutput, TransactWriteItemsInput, TransactWriteItemsOutput, UpdateItemInput, UpdateItemOutput,
+};
+
+/// Kind of item level dynamodb operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// `GetItem` request
+    GetItem,
+    /// `PutItem` request
+    PutItem,
+    /// `DeleteItem` request
+    DeleteItem,
+    /// `UpdateItem` request
+    UpdateItem,
+    /// `Query` request
+    Query,
+    /// `Scan` request
+    Scan,
+    /// `BatchGetItem` request
+    BatchGetItem,
+    /// `BatchWriteItem` request
+    BatchWriteItem,
+    /// `TransactGetItems` request
+    TransactGetItems,
+    /// `TransactWriteItems` request
+    TransactWriteItems,
+    /// `ExecuteStatement` request
+    ExecuteStatement,
+    /// `BatchExecuteStatement` request
+    BatchExecuteStatement,
+    /// `ExecuteTransaction` request
+    ExecuteTransaction,
+}
+
+impl Operation {
+    /// The dynamodb name of the operation
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::GetItem => "GetItem",
+            Self::PutItem => "PutItem",
+            Self::DeleteItem => "DeleteItem",
+            Self::UpdateItem => "UpdateItem",
+            Self::Query => "Query",
+            Self::Scan => "Scan",
+            Self::BatchGetItem => "BatchGetItem",
+            Self::BatchWriteItem => "BatchWriteItem",
+            Self::TransactGetItems => "TransactGetItems",
+            Self::TransactWriteItems => "TransactWriteItems",
+            Self::ExecuteStatement => "ExecuteStatement",
+            Self::BatchExecuteStatement => "BatchExecuteStatement",
+            Self::ExecuteTransaction => "ExecuteTransaction",
+        }
+    }
+
+    /// Whether the operation consumes write capacity rather than read capacity
+    #[must_use]
+    pub const fn is_write(self) -> bool {
+        matches!(self, Self::PutItem | Self::DeleteItem | Self::UpdateItem | Self::BatchWriteItem | Self::TransactWriteItems)
+    }
+}
+
+/// Request input of an item level dynamodb operation
+pub trait Request {
+    /// The operation this input is sent to
+    const OPERATION: Operation;
+
+    /// The table the request targets, if it targets exactly one
+    fn table_name(&self) -> Option<&str>;
+
+    /// The index the request reads from, if any
+    fn index_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// The key condition expression of the request, if any
+    fn key_condition_expression(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether sending the request twice has the same effect as sending it once
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+/// Response output of an item level dynamodb operation
+pub trait Response {
+    /// Capacity consumed by the request, if it was requested with `return_consumed_capacity`
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        &[]
+    }
+
+    /// Number of items returned by the request
+    fn count(&self) -> Option<i64> {
+        None
+    }
+
+    /// Number of items evaluated by the request before any filter was applied
+    fn scanned_count(&self) -> Option<i64> {
+        None
+    }
+}
+
+fn single_table<V>(request_items: &HashMap<String, V>) -> Option<&str> {
+    match request_items.len() {
+        1 => request_items.keys().next().map(String::as_str),
+        _ => None,
+    }
+}
+
+fn len<T>(items: &[T]) -> i64 {
+    i64::try_from(items.len()).unwrap_or(i64::MAX)
+}
+
+macro_rules! table_request {
+    ($($input:ident => $op:ident,)*) => {
+        $(impl Request for $input {
+            const OPERATION: Operation = Operation::$op;
+            fn table_name(&self) -> Option<&str> {
+                Some(&self.table_name)
+            }
+        })*
+    };
+}
+
+table_request! {
+    GetItemInput => GetItem,
+}
+
+/// Whether applying the update expression or statement twice has the same effect as applying it once.
+/// `ADD`, `list_append` and arithmetic build on the stored value, so any of them makes the update non-idempotent.
+/// This errs on the side of caution, e.g. a `-` inside a string literal also counts as arithmetic
+fn is_idempotent_update(expression: &str) -> bool {
+    let mut words = expression.split(|c: char| !c.is_alphanumeric() && c != '_');
+    !expression.contains(&['+', '-'][..]) && !words.any(|word| word.eq_ignore_ascii_case("add") || word.eq_ignore_ascii_case("list_append"))
+}
+
+/// Partiql statements only build on the stored value in `UPDATE` statements
+fn is_idempotent_statement(statement: &str) -> bool {
+    let is_update = statement.trim_start().get(..6).is_some_and(|keyword| keyword.eq_ignore_ascii_case("update"));
+    !is_update || is_idempotent_update(statement)
+}
+
+// a conditional write that was applied fails its condition when sent again
+impl Request for PutItemInput {
+    const OPERATION: Operation = Operation::PutItem;
+    fn table_name(&self) -> Option<&str> {
+        Some(&self.table_name)
+    }
+    fn is_idempotent(&self) -> bool {
+        self.condition_expression.is_none()
+    }
+}
+
+impl Request for DeleteItemInput {
+    const OPERATION: Operation = Operation::DeleteItem;
+    fn table_name(&self) -> Option<&str> {
+        Some(&self.table_name)
+    }
+    fn is_idempotent(&self) -> bool {
+        self.condition_expression.is_none()
+    }
+}
+
+impl Request for UpdateItemInput {
+    const OPERATION: Operation = Operation::UpdateItem;
+    fn table_name(&self) -> Option<&str> {
+        Some(&self.table_name)
+    }
+    fn is_idempotent(&self) -> bool {
+        self.update_expression.as_deref().into_iter().all(is_idempotent_update)
+    }
+}
+
+impl Request for QueryInput {
+    const OPERATION: Operation = Operation::Query;
+    fn table_name(&self) -> Option<&str> {
+        Some(&self.table_name)
+    }
+    fn index_name(&self) -> Option<&str> {
+        self.index_name.as_deref()
+    }
+    fn key_condition_expression(&self) -> Option<&str> {
+        self.key_condition_expression.as_deref()
+    }
+}
+
+impl Request for ScanInput {
+    const OPERATION: Operation = Operation::Scan;
+    fn table_name(&self) -> Option<&str> {
+        Some(&self.table_name)
+    }
+    fn index_name(&self) -> Option<&str> {
+        self.index_name.as_deref()
+    }
+}
+
+impl Request for BatchGetItemInput {
+    const OPERATION: Operation = Operation::BatchGetItem;
+    fn table_name(&self) -> Option<&str> {
+        single_table(&self.request_items)
+    }
+}
+
+impl Request for BatchWriteItemInput {
+    const OPERATION: Operation = Operation::BatchWriteItem;
+    fn table_name(&self) -> Option<&str> {
+        single_table(&self.request_items)
+    }
+}
+
+impl Request for TransactGetItemsInput {
+    const OPERATION: Operation = Operation::TransactGetItems;
+    fn table_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl Request for TransactWriteItemsInput {
+    const OPERATION: Operation = Operation::TransactWriteItems;
+    fn table_name(&self) -> Option<&str> {
+        None
+    }
+    /// Dynamodb only applies a transaction once per client request token
+    fn is_idempotent(&self) -> bool {
+        self.client_request_token.is_some()
+            || self.transact_items.iter().filter_map(|item| item.update.as_ref()).all(|update| is_idempotent_update(&update.update_expression))
+    }
+}
+
+impl Request for ExecuteStatementInput {
+    const OPERATION: Operation = Operation::ExecuteStatement;
+    fn table_name(&self) -> Option<&str> {
+        None
+    }
+    fn is_idempotent(&self) -> bool {
+        is_idempotent_statement(&self.statement)
+    }
+}
+
+impl Request for BatchExecuteStatementInput {
+    const OPERATION: Operation = Operation::BatchExecuteStatement;
+    fn table_name(&self) -> Option<&str> {
+        None
+    }
+    fn is_idempotent(&self) -> bool {
+        self.statements.iter().all(|statement| is_idempotent_statement(&statement.statement))
+    }
+}
+
+impl Request for ExecuteTransactionInput {
+    const OPERATION: Operation = Operation::ExecuteTransaction;
+    fn table_name(&self) -> Option<&str> {
+        None
+    }
+    /// Dynamodb only applies a transaction once per client request token
+    fn is_idempotent(&self) -> bool {
+        self.client_request_token.is_some() || self.transact_statements.iter().all(|statement| is_idempotent_statement(&statement.statement))
+    }
+}
+
+impl Response for GetItemOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_slice()
+    }
+    fn count(&self) -> Option<i64> {
+        Some(self.item.is_some().into())
+    }
+}
+
+impl Response for PutItemOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_slice()
+    }
+}
+
+impl Response for DeleteItemOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_slice()
+    }
+}
+
+impl Response for UpdateItemOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_slice()
+    }
+}
+
+impl Response for QueryOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_slice()
+    }
+    fn count(&self) -> Option<i64> {
+        self.count
+    }
+    fn scanned_count(&self) -> Option<i64> {
+        self.scanned_count
+    }
+}
+
+impl Response for ScanOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_slice()
+    }
+    fn count(&self) -> Option<i64> {
+        self.count
+    }
+    fn scanned_count(&self) -> Option<i64> {
+        self.scanned_count
+    }
+}
+
+impl Response for BatchGetItemOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_deref().unwrap_or_default()
+    }
+    fn count(&self) -> Option<i64> {
+        let responses = self.responses.as_ref()?;
+        Some(responses.values().map(|items| len(items)).sum())
+    }
+}
+
+impl Response for BatchWriteItemOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_deref().unwrap_or_default()
+    }
+}
+
+impl Response for TransactGetItemsOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_deref().unwrap_or_default()
+    }
+    fn count(&self) -> Option<i64> {
+        self.responses.as_deref().map(len)
+    }
+}
+
+impl Response for TransactWriteItemsOutput {
+    fn consumed_capacity(&self) -> &[ConsumedCapacity] {
+        self.consumed_capacity.as_deref().unwrap_or_default()
+    }
+}
+
+impl Response for ExecuteStatementOutput {
+    fn count(&self) -> Option<i64> {
+        self.items.as_deref().map(len)
+    }
+}
+
+impl Response for BatchExecuteStatementOutput {
+    fn count(&self) -> Option<i64> {
+        self.responses.as_deref().map(len)
+    }
+}
+
+impl Response for ExecuteTransactionOutput {
+    fn count(&self) -> Option<i64> {
+        self.responses.as_deref().map(len)
+    }
+}
+
+/// Implements `rusoto_dynamodb::DynamoDb` for a client wrapper with a `client` field.
+/// Item level operations are passed through the given method of the wrapper, along with
+/// a closure that sends the request to the wrapped client. Every other operation is sent
+/// to the wrapped client directly.
+macro_rules! impl_dynamodb {
+    (impl<$($g:ident),*> for $ty:ty where [$($bound:tt)*] via $wrap:ident) => {
+        $crate::operation::impl_dynamodb! {
+            @impl [$($g),*] [$ty] [$($bound)*] $wrap
+            wrapped {
+                batch_execute_statement(BatchExecuteStatementInput) -> BatchExecuteStatementOutput, BatchExecuteStatementError;
+                batch_get_item(BatchGetItemInput) -> BatchGetItemOutput, BatchGetItemError;
+                batch_write_item(BatchWriteItemInput) -> BatchWriteItemOutput, BatchWriteItemError;
+                delete_item(DeleteItemInput) -> DeleteItemOutput, DeleteItemError;
+                execute_statement(ExecuteStatementInput) -> ExecuteStatementOutput, ExecuteStatementError;
+                execute_transaction(ExecuteTransactionInput) -> ExecuteTransactionOutput, ExecuteTransactionError;
+                get_item(GetItemInput) -> GetItemOutput, GetItemError;
+                put_item(PutItemInput) -> PutItemOutput, PutItemError;
+                query(QueryInput) -> QueryOutput, QueryError;
+                scan(ScanInput) -> ScanOutput, ScanError;
+                transact_get_items(TransactGetItemsInput) -> TransactGetItemsOutput, TransactGetItemsError;
+                transact_write_items(TransactWriteItemsInput) -> TransactWriteItemsOutput, TransactWriteItemsError;
+                update_item(UpdateItemInput) -> UpdateItemOutput, UpdateItemError;
+            }
+            plain {
+                create_backup(CreateBackupInput) -> CreateBackupOutput, CreateBackupError;
+                create_global_table(CreateGlobalTableInput) -> CreateGlobalTableOutput, CreateGlobalTableError;
+                create_table(CreateTableInput) -> CreateTableOutput, CreateTableError;
+                delete_backup(DeleteBackupInput) -> DeleteBackupOutput, DeleteBackupError;
+                delete_table(DeleteTableInput) -> DeleteTableOutput, DeleteTableError;
+                describe_backup(DescribeBackupInput) -> DescribeBackupOutput, DescribeBackupError;
+                describe_continuous_backups(DescribeContinuousBackupsInput) -> DescribeContinuousBackupsOutput, DescribeContinuousBackupsError;
+                describe_contributor_insights(DescribeContributorInsightsInput) -> DescribeContributorInsightsOutput, DescribeContributorInsightsError;
+                describe_export(DescribeExportInput) -> DescribeExportOutput, DescribeExportError;
+                describe_global_table(DescribeGlobalTableInput) -> DescribeGlobalTableOutput, DescribeGlobalTableError;
+                describe_global_table_settings(DescribeGlobalTableSettingsInput) -> DescribeGlobalTableSettingsOutput, DescribeGlobalTableSettingsError;
+                describe_kinesis_streaming_destination(DescribeKinesisStreamingDestinationInput) -> DescribeKinesisStreamingDestinationOutput, DescribeKinesisStreamingDestinationError;
+                describe_table(DescribeTableInput) -> DescribeTableOutput, DescribeTableError;
+                describe_table_replica_auto_scaling(DescribeTableReplicaAutoScalingInput) -> DescribeTableReplicaAutoScalingOutput, DescribeTableReplicaAutoScalingError;
+                describe_time_to_live(DescribeTimeToLiveInput) -> DescribeTimeToLiveOutput, DescribeTimeToLiveError;
+                disable_kinesis_streaming_destination(KinesisStreamingDestinationInput) -> KinesisStreamingDestinationOutput, DisableKinesisStreamingDestinationError;
+                enable_kinesis_streaming_destination(KinesisStreamingDestinationInput) -> KinesisStreamingDestinationOutput, EnableKinesisStreamingDestinationError;
+                export_table_to_point_in_time(ExportTableToPointInTimeInput) -> ExportTableToPointInTimeOutput, ExportTableToPointInTimeError;
+                list_backups(ListBackupsInput) -> ListBackupsOutput, ListBackupsError;
+                list_contributor_insights(ListContributorInsightsInput) -> ListContributorInsightsOutput, ListContributorInsightsError;
+                list_exports(ListExportsInput) -> ListExportsOutput, ListExportsError;
+                list_global_tables(ListGlobalTablesInput) -> ListGlobalTablesOutput, ListGlobalTablesError;
+                list_tables(ListTablesInput) -> ListTablesOutput, ListTablesError;
+                list_tags_of_resource(ListTagsOfResourceInput) -> ListTagsOfResourceOutput, ListTagsOfResourceError;
+                restore_table_from_backup(RestoreTableFromBackupInput) -> RestoreTableFromBackupOutput, RestoreTableFromBackupError;
+                restore_table_to_point_in_time(RestoreTableToPointInTimeInput) -> RestoreTableToPointInTimeOutput, RestoreTableToPointInTimeError;
+                update_continuous_backups(UpdateContinuousBackupsInput) -> UpdateContinuousBackupsOutput, UpdateContinuousBackupsError;
+                update_contributor_insights(UpdateContributorInsightsInput) -> UpdateContributorInsightsOutput, UpdateContributorInsightsError;
+                update_global_table(UpdateGlobalTableInput) -> UpdateGlobalTableOutput, UpdateGlobalTableError;
+                update_global_table_settings(UpdateGlobalTableSettingsInput) -> UpdateGlobalTableSettingsOutput, UpdateGlobalTableSettingsError;
+                update_table(UpdateTableInput) -> UpdateTableOutput, UpdateTableError;
+                update_table_replica_auto_scaling(UpdateTableReplicaAutoScalingInput) -> UpdateTableReplicaAutoScalingOutput, UpdateTableReplicaAutoScalingError;
+                update_time_to_live(UpdateTimeToLiveInput) -> UpdateTimeToLiveOutput, UpdateTimeToLiveError;
+            }
+        }
+    };
+    (
+        @impl [$($g:ident),*] [$ty:ty] [$($bound:tt)*] $wrap:ident
+        wrapped { $($w:ident($wi:ident) -> $wo:ident, $we:ident;)* }
+        plain { $($p:ident($pi:ident) -> $po:ident, $pe:ident;)* }
+    ) => {
+        #[::async_trait::async_trait]
+        impl<$($g),*> $crate::dynamodb::DynamoDb for $ty
+        where
+            $($bound)*
+        {
+            $(async fn $w(&self, input: $crate::dynamodb::$wi) -> Result<$crate::dynamodb::$wo, ::rusoto_core::RusotoError<$crate::dynamodb::$we>> {
+                self.$wrap(input, |input| self.client.$w(input)).await
+            })*
+
+            $(async fn $p(&self, input: $crate::dynamodb::$pi) -> Result<$crate::dynamodb::$po, ::rusoto_core::RusotoError<$crate::dynamodb::$pe>> {
+                self.client.$p(input).await
+            })*
+
+            async fn describe_endpoints(&self) -> Result<$crate::dynamodb::DescribeEndpointsResponse, ::rusoto_core::RusotoError<$crate::dynamodb::DescribeEndpointsError>> {
+                self.client.describe_endpoints().await
+            }
+
+            async fn describe_limits(&self) -> Result<$crate::dynamodb::DescribeLimitsOutput, ::rusoto_core::RusotoError<$crate::dynamodb::DescribeLimitsError>> {
+                self.client.describe_limits().await
+            }
+
+            async fn tag_resource(&self, input: $crate::dynamodb::TagResourceInput) -> Result<(), ::rusoto_core::RusotoError<$crate::dynamodb::TagResourceError>> {
+                self.client.tag_resource(input).await
+            }
+
+            async fn untag_resource(&self, input: $crate::dynamodb::UntagResourceInput) -> Result<(), ::rusoto_core::RusotoError<$crate::dynamodb::UntagResourceError>> {
+                self.client.untag_resource(input).await
+            }
+        }
+    };
+}
+
+pub(crate) use impl_dynamodb;
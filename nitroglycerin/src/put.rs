@@ -1,8 +1,22 @@
-use rusoto_dynamodb::{PutItemError, PutItemInput};
+use std::{convert::TryFrom, marker::PhantomData, time::SystemTime};
 
-use crate::{Attributes, DynamoDb, DynamoError, Table};
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::{PutItemError, PutItemInput, PutItemOutput};
+
+use crate::{
+    convert::IntoAttributeValue,
+    meta::{ConsumedCapacityInput, Meta, ReturnConsumedCapacity},
+    timestamp::Timestamped,
+    trace,
+    version::{Condition, Versioned},
+    AttributeError, Attributes, DynamoDb, DynamoError, Table,
+};
 
 /// Trait that declares a type can be built into a put item request
+///
+/// Putting an item fills in its `#[nitro(created_at)]` and `#[nitro(updated_at)]` timestamps, and writes it with the next
+/// `#[nitro(version)]` on the condition that the stored version has not changed. Hand written item types opt out of both with
+/// empty [`Versioned`] and [`Timestamped`] implementations
 pub trait Put<'d, D: 'd + ?Sized>: Table {
     /// The builder type that performs the put item request
     type Builder;
@@ -10,44 +24,182 @@ pub trait Put<'d, D: 'd + ?Sized>: Table {
     fn put(self, client: &'d D) -> Self::Builder;
 }
 
-impl<'d, D: 'd + ?Sized, T: Table + Into<Attributes>> Put<'d, D> for T {
-    type Builder = Expr<'d, D>;
-    fn put(self, client: &'d D) -> Self::Builder {
+impl<'d, D: 'd + ?Sized, T: Table + Versioned + Timestamped + Into<Attributes>> Put<'d, D> for T {
+    type Builder = Expr<'d, D, T>;
+    fn put(mut self, client: &'d D) -> Self::Builder {
+        self.touch(SystemTime::now());
+        let version = self.item_version_attribute().map(|name| (name, self.version(), self.next_version()));
         let input = PutItemInput {
             table_name: T::table_name(),
             item: self.into(),
             ..PutItemInput::default()
         };
+        let mut expr = Expr::new(client, input);
+
+        if let Some((name, version, next_version)) = version {
+            let Some(next_version) = next_version else {
+                expr.version_overflow = true;
+                return expr;
+            };
+            expr.input.item.insert(name.to_owned(), next_version.into_av());
+            let Condition { expression, names, values } = Condition::new(name, version);
+            expr.input.condition_expression = Some(expression);
+            expr.input.expression_attribute_names = Some(names);
+            expr.input.expression_attribute_values = values;
+        }
 
-        Expr::new(client, input)
+        expr
     }
 }
 
 /// Final output of a put item builder chain
-pub struct Expr<'d, D: 'd + ?Sized> {
+pub struct Expr<'d, D: 'd + ?Sized, T> {
     client: &'d D,
     input: PutItemInput,
+    version_overflow: bool,
+    _phantom: PhantomData<T>,
 }
 
-impl<'d, D: 'd + ?Sized> Expr<'d, D> {
+impl<'d, D: 'd + ?Sized, T> Expr<'d, D, T> {
     /// Create a new `Expr`
     pub const fn new(client: &'d D, input: PutItemInput) -> Self {
-        Self { client, input }
+        Self {
+            client,
+            input,
+            version_overflow: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Return the item that was overwritten by the put item request, if there was one
+    #[must_use]
+    pub fn return_all_old(self) -> ReturnAllOld<'d, D, T> {
+        let Self { client, mut input, version_overflow, .. } = self;
+        input.return_values = Some("ALL_OLD".to_owned());
+        ReturnAllOld {
+            client,
+            input,
+            version_overflow,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Report the capacity consumed by the request in the [`Meta`] returned by `execute_with_meta`
+    #[must_use]
+    pub fn return_consumed_capacity(mut self, return_consumed_capacity: ReturnConsumedCapacity) -> Self {
+        self.input.set_return_consumed_capacity(return_consumed_capacity);
+        self
+    }
+
+    /// Report the size of the affected item collection in the [`Meta`] returned by `execute_with_meta`
+    #[must_use]
+    pub fn return_item_collection_metrics(mut self) -> Self {
+        self.input.return_item_collection_metrics = Some("SIZE".to_owned());
+        self
     }
 }
 
-impl<'d, D: 'd + ?Sized> Expr<'d, D>
+impl<'d, D: 'd + ?Sized, T> Expr<'d, D, T>
 where
     D: DynamoDb,
     for<'a> &'a D: Send,
+    T: Send,
 {
     /// Execute the put item request
     ///
     /// # Errors
-    /// Will error if the dynamodb request fails
+    /// Will error if the dynamodb request fails, or when putting a versioned item with [`DynamoError::VersionMismatch`]
+    /// if the stored version has changed or [`DynamoError::VersionOverflow`] if there is no next version
     pub async fn execute(self) -> Result<(), DynamoError<PutItemError>> {
-        let Self { client, input } = self;
-        client.put_item(input).await?;
+        self.execute_with_meta().await?;
         Ok(())
     }
+
+    /// Execute the put item request, returning the consumed capacity and item collection metrics
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails, or when putting a versioned item with [`DynamoError::VersionMismatch`]
+    /// if the stored version has changed or [`DynamoError::VersionOverflow`] if there is no next version
+    pub async fn execute_with_meta(self) -> Result<Meta, DynamoError<PutItemError>> {
+        let Self { client, input, version_overflow, .. } = self;
+        let span = trace::span(&input);
+        trace::instrument(span, async move {
+            let mut output = put_item(client, input, version_overflow).await?;
+            Ok(meta(&mut output))
+        })
+        .await
+    }
+}
+
+/// Put item builder which will return the overwritten item
+pub struct ReturnAllOld<'d, D: 'd + ?Sized, T> {
+    client: &'d D,
+    input: PutItemInput,
+    version_overflow: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<'d, D: 'd + ?Sized, T> ReturnAllOld<'d, D, T>
+where
+    D: DynamoDb,
+    for<'a> &'a D: Send,
+    T: TryFrom<Attributes, Error = AttributeError> + Send,
+{
+    /// Execute the put item request returning the contents of the overwritten item
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails, the old item could not be parsed, or when putting a versioned item with
+    /// [`DynamoError::VersionMismatch`] if the stored version has changed or [`DynamoError::VersionOverflow`] if there is no next version
+    pub async fn execute(self) -> Result<Option<T>, DynamoError<PutItemError>> {
+        Ok(self.execute_with_meta().await?.0)
+    }
+
+    /// Execute the put item request returning the contents of the overwritten item, along with the consumed capacity
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails, the old item could not be parsed, or when putting a versioned item with
+    /// [`DynamoError::VersionMismatch`] if the stored version has changed or [`DynamoError::VersionOverflow`] if there is no next version
+    pub async fn execute_with_meta(self) -> Result<(Option<T>, Meta), DynamoError<PutItemError>> {
+        let Self { client, input, version_overflow, .. } = self;
+        let span = trace::span(&input);
+        let (table_name, key) = (input.table_name.clone(), key_attributes(&input.item));
+        trace::instrument(span, async move {
+            let mut output = put_item(client, input, version_overflow).await?;
+            let meta = meta(&mut output);
+            let item = output.attributes.map(T::try_from).transpose().map_err(DynamoError::item(&table_name, key))?;
+            Ok((item, meta))
+        })
+        .await
+    }
+}
+
+/// The put request does not know the key schema of the table, so this keeps every attribute of the written item
+/// that could be part of the key: key attributes are always strings, numbers or binary
+fn key_attributes(item: &Attributes) -> Attributes {
+    item.iter()
+        .filter(|(_, value)| value.s.is_some() || value.n.is_some() || value.b.is_some())
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+fn meta(output: &mut PutItemOutput) -> Meta {
+    Meta {
+        consumed_capacity: output.consumed_capacity.take(),
+        item_collection_metrics: output.item_collection_metrics.take(),
+        ..Meta::default()
+    }
+}
+
+async fn put_item<D: ?Sized + DynamoDb>(client: &D, input: PutItemInput, version_overflow: bool) -> Result<PutItemOutput, DynamoError<PutItemError>>
+where
+    for<'a> &'a D: Send,
+{
+    if version_overflow {
+        return Err(DynamoError::VersionOverflow);
+    }
+    let versioned = input.condition_expression.is_some();
+    client.put_item(input).await.map_err(|err| match err {
+        RusotoError::Service(PutItemError::ConditionalCheckFailed(_)) if versioned => DynamoError::VersionMismatch,
+        err => err.into(),
+    })
 }
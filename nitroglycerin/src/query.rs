@@ -2,7 +2,13 @@ use std::{convert::TryFrom, marker::PhantomData, ops::RangeInclusive};
 
 use rusoto_dynamodb::{DynamoDb, QueryError, QueryInput};
 
-use crate::{convert::IntoAttributeValue, AttributeError, Attributes, DynamoError, Table, TableIndex};
+use crate::{
+    convert::IntoAttributeValue,
+    key::PrimaryKey,
+    meta::{ConsumedCapacityInput, Meta, ReturnConsumedCapacity},
+    trace,
+    AttributeError, Attributes, DynamoError, Table, TableIndex,
+};
 
 /// create a [`QueryInput`] using the table and partition key
 pub fn new_input<I: TableIndex, K: IntoAttributeValue>(key_name: &str, key_value: K) -> QueryInput {
@@ -26,13 +32,13 @@ pub trait Query<'d, D: 'd + ?Sized>: TableIndex {
 }
 
 /// Sort key expression builder
-pub struct BuilderSort<'d, D: 'd + ?Sized, SortKey, Index> {
+pub struct BuilderSort<'d, D: 'd + ?Sized, SortKey, Index, Item = Index> {
     client: &'d D,
     input: QueryInput,
-    _phantom: PhantomData<(SortKey, Index)>,
+    _phantom: PhantomData<(SortKey, Index, Item)>,
 }
 
-impl<'d, D: 'd + ?Sized, S, I> BuilderSort<'d, D, S, I> {
+impl<'d, D: 'd + ?Sized, S, I, T> BuilderSort<'d, D, S, I, T> {
     /// Create a new `BuilderSort`
     pub fn new(client: &'d D, mut input: QueryInput, sort_key: &str) -> Self {
         input.expression_attribute_names.as_mut().map(|n| n.insert("#1".to_owned(), sort_key.to_owned()));
@@ -40,7 +46,7 @@ impl<'d, D: 'd + ?Sized, S, I> BuilderSort<'d, D, S, I> {
     }
 }
 
-impl<'d, D: 'd + ?Sized, S, I> BuilderSort<'d, D, S, I>
+impl<'d, D: 'd + ?Sized, S, I, T> BuilderSort<'d, D, S, I, T>
 where
     S: IntoAttributeValue,
 {
@@ -54,48 +60,48 @@ where
             v.insert(key.to_owned(), sort.into_av());
         }
     }
-    fn build(self) -> Expr<'d, D, I> {
+    fn build(self) -> Expr<'d, D, I, T> {
         let Self { client, input, _phantom } = self;
         Expr::new(client, input)
     }
 
     /// Query for sort key equal
-    pub fn equal(mut self, sort: impl Into<S>) -> Expr<'d, D, I> {
+    pub fn equal(mut self, sort: impl Into<S>) -> Expr<'d, D, I, T> {
         self.push_expr("AND #1 = :1");
         self.push_value(":1", sort.into());
         self.build()
     }
 
     /// Query for sort key less than
-    pub fn less_than(mut self, sort: impl Into<S>) -> Expr<'d, D, I> {
+    pub fn less_than(mut self, sort: impl Into<S>) -> Expr<'d, D, I, T> {
         self.push_expr("AND #1 < :1");
         self.push_value(":1", sort.into());
         self.build()
     }
 
     /// Query for sort key less than or equal
-    pub fn less_than_or_equal(mut self, sort: impl Into<S>) -> Expr<'d, D, I> {
+    pub fn less_than_or_equal(mut self, sort: impl Into<S>) -> Expr<'d, D, I, T> {
         self.push_expr("AND #1 <= :1");
         self.push_value(":1", sort.into());
         self.build()
     }
 
     /// Query for sort key greater than
-    pub fn greater_than(mut self, sort: impl Into<S>) -> Expr<'d, D, I> {
+    pub fn greater_than(mut self, sort: impl Into<S>) -> Expr<'d, D, I, T> {
         self.push_expr("AND #1 > :1");
         self.push_value(":1", sort.into());
         self.build()
     }
 
     /// Query for sort key greater than or equal
-    pub fn greater_than_or_equal(mut self, sort: impl Into<S>) -> Expr<'d, D, I> {
+    pub fn greater_than_or_equal(mut self, sort: impl Into<S>) -> Expr<'d, D, I, T> {
         self.push_expr("AND #1 >= :1");
         self.push_value(":1", sort.into());
         self.build()
     }
 
     /// Query for sort key between
-    pub fn between(mut self, sort: RangeInclusive<impl Into<S>>) -> Expr<'d, D, I> {
+    pub fn between(mut self, sort: RangeInclusive<impl Into<S>>) -> Expr<'d, D, I, T> {
         let (sort1, sort2) = sort.into_inner();
 
         self.push_expr("AND #1 BETWEEN :1 AND :2");
@@ -105,7 +111,7 @@ where
     }
 
     /// Query for sort key beginning with
-    pub fn begins_with(mut self, sort: impl Into<S>) -> Expr<'d, D, I> {
+    pub fn begins_with(mut self, sort: impl Into<S>) -> Expr<'d, D, I, T> {
         self.push_expr("AND begins_with(#1, :1)");
         self.push_value(":1", sort.into());
         self.build()
@@ -113,16 +119,35 @@ where
 }
 
 /// Final output of a query builder chain
-pub struct Expr<'d, D: 'd + ?Sized, Index> {
+///
+/// `Item` is the type each returned item is parsed into, which defaults to the index type itself
+///
+/// Items that fail to parse are reported by the primary key of the table, so it must implement [`PrimaryKey`]
+pub struct Expr<'d, D: 'd + ?Sized, Index, Item = Index> {
     client: &'d D,
     input: QueryInput,
-    _phantom: PhantomData<Index>,
+    skip_invalid: bool,
+    _phantom: PhantomData<(Index, Item)>,
 }
 
-impl<'d, D: 'd + ?Sized, I> Expr<'d, D, I> {
+/// A returned item that could not be parsed
+#[derive(Debug)]
+pub struct InvalidItem {
+    /// The key attributes of the item, as far as they are known
+    pub key: Attributes,
+    /// The parse error
+    pub error: AttributeError,
+}
+
+impl<'d, D: 'd + ?Sized, I, T> Expr<'d, D, I, T> {
     /// Create a new `Expr`
     pub const fn new(client: &'d D, input: QueryInput) -> Self {
-        Self { client, input, _phantom: PhantomData }
+        Self {
+            client,
+            input,
+            skip_invalid: false,
+            _phantom: PhantomData,
+        }
     }
 
     /// Enable consistent read for the query request
@@ -131,21 +156,90 @@ impl<'d, D: 'd + ?Sized, I> Expr<'d, D, I> {
         self.input.consistent_read = Some(true);
         self
     }
+
+    /// Report the capacity consumed by the query request in the [`Meta`] returned by `execute_with_meta`
+    #[must_use]
+    pub fn return_consumed_capacity(mut self, return_consumed_capacity: ReturnConsumedCapacity) -> Self {
+        self.input.set_return_consumed_capacity(return_consumed_capacity);
+        self
+    }
+
+    /// Leave out returned items that cannot be parsed, instead of failing the whole query
+    ///
+    /// Use `execute_collect_invalid` to find out which items were left out
+    #[must_use]
+    pub const fn skip_invalid(mut self) -> Self {
+        self.skip_invalid = true;
+        self
+    }
 }
 
-impl<'d, D: 'd + ?Sized, I> Expr<'d, D, I>
+impl<'d, D: 'd + ?Sized, I, T> Expr<'d, D, I, T>
 where
     D: DynamoDb,
     &'d D: Send,
-    I: TryFrom<Attributes, Error = AttributeError> + Send,
+    I: TableIndex + Send,
+    I::Table: PrimaryKey,
+    T: TryFrom<Attributes, Error = AttributeError> + Send,
 {
     /// Execute the query request
     ///
     /// # Errors
     /// Will error if the dynamodb request fails or the resulting items could not be parsed
-    pub async fn execute(self) -> Result<Vec<I>, DynamoError<QueryError>> {
-        let output = self.client.query(self.input).await?;
-        let items = output.items.unwrap_or_else(Vec::new).into_iter();
-        Ok(items.map(I::try_from).collect::<Result<_, _>>()?)
+    pub async fn execute(self) -> Result<Vec<T>, DynamoError<QueryError>> {
+        Ok(self.execute_with_meta().await?.0)
     }
+
+    /// Execute the query request, also returning the consumed capacity and item counts
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails or the resulting items could not be parsed
+    pub async fn execute_with_meta(self) -> Result<(Vec<T>, Meta), DynamoError<QueryError>> {
+        let (items, _, meta) = self.execute_inner(false).await?;
+        Ok((items, meta))
+    }
+
+    /// Execute the query request, returning the items that could not be parsed alongside the ones that could
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails
+    pub async fn execute_collect_invalid(self) -> Result<(Vec<T>, Vec<InvalidItem>, Meta), DynamoError<QueryError>> {
+        self.execute_inner(true).await
+    }
+
+    async fn execute_inner(self, collect_invalid: bool) -> Result<(Vec<T>, Vec<InvalidItem>, Meta), DynamoError<QueryError>> {
+        let Self { client, input, skip_invalid, _phantom } = self;
+        let span = trace::span(&input);
+        let table_name = input.table_name.clone();
+        // index queries return the table's key attributes too, which is what identifies the item
+        let key_names = [Some(<I::Table as PrimaryKey>::partition_key_name()), <I::Table as PrimaryKey>::sort_key_name()];
+        trace::instrument(span, async move {
+            let output = client.query(input).await?;
+            let meta = Meta {
+                consumed_capacity: output.consumed_capacity,
+                count: output.count,
+                scanned_count: output.scanned_count,
+                ..Meta::default()
+            };
+            let items = output.items.unwrap_or_else(Vec::new);
+            let mut parsed = Vec::with_capacity(items.len());
+            let mut invalid = Vec::new();
+            for item in items {
+                let key = item_key(&key_names, &item);
+                match T::try_from(item) {
+                    Ok(item) => parsed.push(item),
+                    Err(error) if collect_invalid => invalid.push(InvalidItem { key, error }),
+                    Err(_) if skip_invalid => {}
+                    Err(error) => return Err(DynamoError::item(&table_name, key)(error)),
+                }
+            }
+            Ok((parsed, invalid, meta))
+        })
+        .await
+    }
+}
+
+/// The named key attributes of the item
+fn item_key(key_names: &[Option<&str>], item: &Attributes) -> Attributes {
+    key_names.iter().flatten().filter_map(|&name| Some((name.to_owned(), item.get(name)?.clone()))).collect()
 }
@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    future::Future,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::{
+    BatchExecuteStatementInput, BatchExecuteStatementOutput, BatchGetItemInput, BatchGetItemOutput, BatchWriteItemInput, BatchWriteItemOutput, DeleteItemInput, DeleteItemOutput,
+    ExecuteStatementInput, ExecuteStatementOutput, ExecuteTransactionInput, ExecuteTransactionOutput, GetItemInput, GetItemOutput, PutItemInput, PutItemOutput, QueryInput, QueryOutput,
+    ScanInput, ScanOutput, TransactGetItemsInput, TransactGetItemsOutput, TransactWriteItemsInput, TransactWriteItemsOutput, UpdateItemInput, UpdateItemOutput,
+};
+use thiserror::Error;
+
+use crate::{
+    error::{Classify, ErrorKind},
+    operation::{impl_dynamodb, Request, Response},
+    trace,
+};
+
+/// Error returned once a [`RetryPolicy`] gives up on a request
+#[derive(Debug, Error)]
+#[error("failed after {attempts} attempts: {error}")]
+pub struct RetryError<E> {
+    /// Number of times the request was sent
+    pub attempts: u32,
+    /// The error returned by the last attempt
+    #[source]
+    pub error: E,
+}
+
+/// How often and how long to wait before retrying throttled or transient request failures
+///
+/// The delay before retry `n` is `base_delay * 2^(n - 1)`, capped at `max_delay`.
+/// With jitter enabled, a random delay between zero and that value is used instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    deadline: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(25),
+            max_delay: Duration::from_secs(20),
+            jitter: true,
+            deadline: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    #[must_use]
+    pub fn never() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Maximum number of times a request is sent, including the first attempt
+    #[must_use]
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Delay before the first retry
+    #[must_use]
+    pub const fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Upper bound of the delay between two attempts
+    #[must_use]
+    pub const fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Whether to randomise delays, so throttled clients don't retry in lockstep
+    #[must_use]
+    pub const fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Total time after the first attempt beyond which no more retries are made
+    #[must_use]
+    pub const fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        let exp = 2_u32.checked_pow(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        let delay = self.base_delay.checked_mul(exp).unwrap_or(self.max_delay).min(self.max_delay);
+        if self.jitter {
+            delay.mul_f64(fastrand::f64())
+        } else {
+            delay
+        }
+    }
+
+    /// Run the request, retrying it according to this policy for as long as it fails with a retryable error
+    ///
+    /// # Errors
+    /// Will error with the last error once the request fails with an error that is not retryable,
+    /// `max_attempts` is reached, or the next retry would start after the deadline
+    pub async fn run<T, E, F, Fut>(&self, f: F) -> Result<T, RetryError<E>>
+    where
+        E: Error + Classify + 'static,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let (result, attempts) = self.attempt(f, E::is_retryable).await;
+        result.map_err(|error| RetryError { attempts, error })
+    }
+
+    /// Send the request, retrying it according to this policy and sending the part of it dynamodb left unprocessed again.
+    /// Once the policy gives up on unprocessed items, the responses so far are returned, along with the items still unprocessed.
+    ///
+    /// Requests that are not [idempotent](Request::is_idempotent) are only retried when throttled,
+    /// since any other failure may have happened after dynamodb applied the request
+    pub(crate) async fn send<I, R, E, F, Fut>(&self, input: I, f: F) -> (Result<R, RusotoError<E>>, u32)
+    where
+        I: Request + Clone + Send,
+        R: Resubmit<I> + Default + Send,
+        E: Error + Classify + Send + 'static,
+        F: Fn(I) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<R, RusotoError<E>>> + Send,
+    {
+        let idempotent = input.is_idempotent();
+        let state = &Mutex::new((input, R::default()));
+        let attempt = || async {
+            let input = lock(state).0.clone();
+            let output = f(input).await.map_err(Attempt::Failed)?;
+            let (pending, responses) = &mut *lock(state);
+            let unprocessed = output.unprocessed(pending);
+            responses.merge(output);
+            if let Some(input) = unprocessed {
+                *pending = input;
+                return Err(Attempt::Unprocessed);
+            }
+            Ok(())
+        };
+        let retryable = |attempt: &Attempt<E>| match attempt {
+            Attempt::Failed(err) if idempotent => err.is_retryable(),
+            Attempt::Failed(err) => err.kind() == ErrorKind::Throttled,
+            Attempt::Unprocessed => true,
+        };
+
+        let (result, attempts) = self.attempt(attempt, retryable).await;
+        let result = match result {
+            Ok(()) | Err(Attempt::Unprocessed) => Ok(std::mem::take(&mut lock(state).1)),
+            Err(Attempt::Failed(err)) => Err(err),
+        };
+        (result, attempts)
+    }
+
+    async fn attempt<T, E, F, Fut>(&self, mut f: F, retryable: impl Fn(&E) -> bool) -> (Result<T, E>, u32)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let start = Instant::now();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let error = match f().await {
+                Ok(t) => return (Ok(t), attempts),
+                Err(error) => error,
+            };
+
+            let delay = self.delay(attempts);
+            let past_deadline = self.deadline.is_some_and(|deadline| start.elapsed() + delay > deadline);
+            if !retryable(&error) || attempts >= self.max_attempts || past_deadline {
+                return (Err(error), attempts);
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Outcome of a single attempt of [`RetryPolicy::send`] that did not fully succeed
+enum Attempt<E> {
+    Failed(RusotoError<E>),
+    Unprocessed,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Response that can hand part of its request back as unprocessed, to be sent again
+pub(crate) trait Resubmit<I> {
+    /// The part of `input` that was left unprocessed, if any
+    fn unprocessed(&self, _input: &I) -> Option<I> {
+        None
+    }
+
+    /// Add the response to a resent request to this one
+    fn merge(&mut self, next: Self)
+    where
+        Self: Sized,
+    {
+        *self = next;
+    }
+}
+
+macro_rules! fully_processed {
+    ($($input:ident => $output:ident,)*) => {
+        $(impl Resubmit<$input> for $output {})*
+    };
+}
+
+fully_processed! {
+    GetItemInput => GetItemOutput,
+    PutItemInput => PutItemOutput,
+    DeleteItemInput => DeleteItemOutput,
+    UpdateItemInput => UpdateItemOutput,
+    QueryInput => QueryOutput,
+    ScanInput => ScanOutput,
+    TransactGetItemsInput => TransactGetItemsOutput,
+    TransactWriteItemsInput => TransactWriteItemsOutput,
+    ExecuteStatementInput => ExecuteStatementOutput,
+    BatchExecuteStatementInput => BatchExecuteStatementOutput,
+    ExecuteTransactionInput => ExecuteTransactionOutput,
+}
+
+fn extend<T>(into: &mut Option<Vec<T>>, next: Option<Vec<T>>) {
+    into.get_or_insert_with(Vec::new).extend(next.into_iter().flatten());
+}
+
+fn extend_tables<T>(into: &mut Option<HashMap<String, Vec<T>>>, next: Option<HashMap<String, Vec<T>>>) {
+    let into = into.get_or_insert_with(HashMap::new);
+    for (table_name, values) in next.into_iter().flatten() {
+        into.entry(table_name).or_default().extend(values);
+    }
+}
+
+impl Resubmit<BatchWriteItemInput> for BatchWriteItemOutput {
+    fn unprocessed(&self, input: &BatchWriteItemInput) -> Option<BatchWriteItemInput> {
+        let request_items = self.unprocessed_items.clone().filter(|items| items.values().any(|requests| !requests.is_empty()))?;
+        Some(BatchWriteItemInput { request_items, ..input.clone() })
+    }
+
+    fn merge(&mut self, next: Self) {
+        extend(&mut self.consumed_capacity, next.consumed_capacity);
+        extend_tables(&mut self.item_collection_metrics, next.item_collection_metrics);
+        self.unprocessed_items = next.unprocessed_items;
+    }
+}
+
+impl Resubmit<BatchGetItemInput> for BatchGetItemOutput {
+    fn unprocessed(&self, input: &BatchGetItemInput) -> Option<BatchGetItemInput> {
+        let request_items = self.unprocessed_keys.clone().filter(|keys| keys.values().any(|keys| !keys.keys.is_empty()))?;
+        Some(BatchGetItemInput { request_items, ..input.clone() })
+    }
+
+    fn merge(&mut self, next: Self) {
+        extend(&mut self.consumed_capacity, next.consumed_capacity);
+        extend_tables(&mut self.responses, next.responses);
+        self.unprocessed_keys = next.unprocessed_keys;
+    }
+}
+
+/// Dynamodb client wrapper that retries every item level request according to a [`RetryPolicy`]
+///
+/// Items and keys that batch writes and batch gets leave unprocessed are sent again, with the same delays as retries.
+/// When the policy gives up on them, the response holds the ones still unprocessed.
+///
+/// Requests that are not idempotent, such as updates that `ADD` to a counter or conditional puts and deletes, are only
+/// retried when throttled.
+/// Any other failure, like a timeout, may have happened after dynamodb applied the update, so sending it again
+/// could apply it twice. Transactions are retried like any other request if they carry a client request token.
+///
+/// The wrapper returns the same errors as the client it wraps, so a failed request only reports the error of the
+/// last attempt. With the `tracing` feature, the number of retries is recorded on the span of the request.
+#[derive(Debug, Clone)]
+pub struct Retry<D> {
+    client: D,
+    policy: RetryPolicy,
+}
+
+impl<D> Retry<D> {
+    /// Wrap the client so its requests are retried according to the policy
+    pub const fn new(client: D, policy: RetryPolicy) -> Self {
+        Self { client, policy }
+    }
+
+    /// The wrapped client
+    pub const fn client(&self) -> &D {
+        &self.client
+    }
+
+    /// The policy requests are retried with
+    pub const fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Unwrap the client and policy
+    pub fn into_parts(self) -> (D, RetryPolicy) {
+        (self.client, self.policy)
+    }
+}
+
+impl<D: Sync> Retry<D> {
+    async fn retry<I, R, E, F, Fut>(&self, input: I, f: F) -> Result<R, RusotoError<E>>
+    where
+        I: Request + Clone + Send + Sync,
+        R: Response + Resubmit<I> + Default + Send,
+        E: Error + Classify + Send + 'static,
+        F: Fn(I) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<R, RusotoError<E>>> + Send,
+    {
+        let (result, attempts) = self.policy.send(input, f).await;
+        trace::record_retries(attempts - 1);
+        result
+    }
+}
+
+impl_dynamodb!(impl<D> for Retry<D> where [D: rusoto_dynamodb::DynamoDb + Send + Sync] via retry);
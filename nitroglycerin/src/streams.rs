@@ -0,0 +1,161 @@
+use std::convert::TryFrom;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{error, AttributeError, Attributes};
+
+/// Error returned when decoding stream records
+#[derive(Debug, Error)]
+pub enum StreamError {
+    /// Error occured because the event was not valid json
+    #[error("could not parse stream event: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Error occurs when the stream does not include the image the change needs,
+    /// such as the new image of an insert on a `KEYS_ONLY` stream
+    #[error("stream record for item {} has no {image}", error::display_key(.key))]
+    MissingImage {
+        /// The key attributes of the item
+        key: Attributes,
+        /// The name of the missing image, `NewImage` or `OldImage`
+        image: &'static str,
+    },
+
+    /// Error occurs when an image could not be parsed
+    #[error("could not parse {image} of item {}: {source}", error::display_key(.key))]
+    ParseError {
+        /// The key attributes of the item
+        key: Attributes,
+        /// The name of the image, `NewImage` or `OldImage`
+        image: &'static str,
+        /// The parse error
+        source: AttributeError,
+    },
+}
+
+/// The kind of modification a stream record describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EventName {
+    /// A new item was added to the table
+    Insert,
+    /// One or more attributes of an existing item were modified
+    Modify,
+    /// An item was deleted from the table
+    Remove,
+}
+
+/// The item level data of a stream record
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StreamRecord {
+    /// The key attributes of the modified item
+    #[serde(default)]
+    pub keys: Attributes,
+    /// The item after it was modified, if the stream view type includes new images
+    pub new_image: Option<Attributes>,
+    /// The item before it was modified, if the stream view type includes old images
+    pub old_image: Option<Attributes>,
+    /// The position of the record in the stream
+    pub sequence_number: Option<String>,
+}
+
+/// A record of a dynamodb stream
+///
+/// Records have the same json shape in lambda events and `GetRecords` responses of the streams api
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Record {
+    /// Unique identifier of the event
+    #[serde(rename = "eventID")]
+    pub event_id: Option<String>,
+    /// The kind of modification
+    #[serde(rename = "eventName")]
+    pub event_name: EventName,
+    /// The item level data
+    pub dynamodb: StreamRecord,
+}
+
+/// A lambda event delivering a batch of dynamodb stream records
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Event {
+    /// The records of the batch, in stream order
+    #[serde(rename = "Records")]
+    pub records: Vec<Record>,
+}
+
+impl Event {
+    /// Parse the json of a lambda event
+    ///
+    /// # Errors
+    /// Will error if the json is not a dynamodb stream event
+    pub fn from_json(json: &str) -> Result<Self, StreamError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Decode every record of the batch into a typed change
+    pub fn changes<T>(self) -> impl Iterator<Item = Result<Change<T>, StreamError>>
+    where
+        T: TryFrom<Attributes, Error = AttributeError>,
+    {
+        self.records.into_iter().map(Record::change)
+    }
+}
+
+/// The item that was removed by a [`Change::Remove`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Removed<T> {
+    /// The item as it was before it was removed, if the stream view type includes old images
+    Item(T),
+    /// The key attributes of the removed item, if it does not
+    Key(Attributes),
+}
+
+/// A typed change to an item, decoded from a stream [`Record`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<T> {
+    /// A new item was added to the table
+    Insert(T),
+    /// An existing item was modified
+    Modify {
+        /// The item before it was modified, if the stream view type includes old images
+        old: Option<T>,
+        /// The item after it was modified
+        new: T,
+    },
+    /// An item was deleted from the table
+    Remove(Removed<T>),
+}
+
+impl Record {
+    /// Decode the images of the record into a typed change
+    ///
+    /// # Errors
+    /// Will error if the record does not include the new image of an insert or modification,
+    /// or if an image could not be parsed
+    pub fn change<T>(self) -> Result<Change<T>, StreamError>
+    where
+        T: TryFrom<Attributes, Error = AttributeError>,
+    {
+        let StreamRecord { keys, new_image, old_image, .. } = self.dynamodb;
+        let old = old_image.map(|item| parse(&keys, "OldImage", item)).transpose()?;
+        match self.event_name {
+            EventName::Remove => Ok(Change::Remove(old.map_or(Removed::Key(keys), Removed::Item))),
+            event_name => {
+                let Some(new_image) = new_image else { return Err(StreamError::MissingImage { key: keys, image: "NewImage" }) };
+                let new = parse(&keys, "NewImage", new_image)?;
+                match event_name {
+                    EventName::Insert => Ok(Change::Insert(new)),
+                    _ => Ok(Change::Modify { old, new }),
+                }
+            }
+        }
+    }
+}
+
+fn parse<T>(keys: &Attributes, image: &'static str, item: Attributes) -> Result<T, StreamError>
+where
+    T: TryFrom<Attributes, Error = AttributeError>,
+{
+    T::try_from(item).map_err(|source| StreamError::ParseError { key: keys.clone(), image, source })
+}
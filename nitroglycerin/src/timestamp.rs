@@ -0,0 +1,118 @@
+use std::{
+    convert::TryFrom,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusoto_dynamodb::AttributeValue;
+
+/// Types that can be filled in by `#[nitro(created_at)]` and `#[nitro(updated_at)]`
+pub trait Timestamp {
+    /// Create the timestamp for the given time
+    fn from_system_time(time: SystemTime) -> Self;
+
+    /// Whether the timestamp has not been set yet
+    fn is_unset(&self) -> bool;
+}
+
+/// Seconds since the unix epoch
+impl Timestamp for i64 {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.duration_since(UNIX_EPOCH).map_or(0, |d| Self::try_from(d.as_secs()).unwrap_or(Self::MAX))
+    }
+
+    fn is_unset(&self) -> bool {
+        *self == 0
+    }
+}
+
+/// Seconds since the unix epoch
+impl Timestamp for u64 {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+    }
+
+    fn is_unset(&self) -> bool {
+        *self == 0
+    }
+}
+
+impl<T: Timestamp> Timestamp for Option<T> {
+    fn from_system_time(time: SystemTime) -> Self {
+        Some(T::from_system_time(time))
+    }
+
+    // `Option::is_none_or` is only stable since rust 1.82
+    #[allow(clippy::unnecessary_map_or)]
+    fn is_unset(&self) -> bool {
+        self.as_ref().map_or(true, T::is_unset)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::DateTime<chrono::Utc> {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+
+    fn is_unset(&self) -> bool {
+        self.timestamp() == 0
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::DateTime<chrono::Local> {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+
+    fn is_unset(&self) -> bool {
+        self.timestamp() == 0
+    }
+}
+
+impl Timestamp for SystemTime {
+    fn from_system_time(time: SystemTime) -> Self {
+        time
+    }
+
+    fn is_unset(&self) -> bool {
+        *self == UNIX_EPOCH
+    }
+}
+
+/// Trait that fills in the audit timestamps of an item. Implemented by the `Attributes` derive
+///
+/// The timestamps are only filled in by [`put`](crate::DynamoDb::put) and by update requests that `touch` the item.
+/// Items copied by a table transfer keep the timestamps they were read with
+pub trait Timestamped {
+    /// Set the `#[nitro(updated_at)]` field to `now`, along with the `#[nitro(created_at)]` field if it is unset.
+    /// Recurses into `#[nitro(flatten)]` fields
+    fn touch(&mut self, _now: SystemTime) {}
+
+    /// Name and value of the `#[nitro(created_at)]` attribute at `now`
+    #[must_use]
+    fn created_at(_now: SystemTime) -> Option<(&'static str, AttributeValue)> {
+        None
+    }
+
+    /// Name and value of the `#[nitro(updated_at)]` attribute at `now`
+    #[must_use]
+    fn updated_at(_now: SystemTime) -> Option<(&'static str, AttributeValue)> {
+        None
+    }
+
+    /// Name and value of the `#[nitro(created_at)]` attribute of this item at `now`. Tagged enums use their variant's
+    #[must_use]
+    fn item_created_at(&self, now: SystemTime) -> Option<(&'static str, AttributeValue)> {
+        Self::created_at(now)
+    }
+
+    /// Name and value of the `#[nitro(updated_at)]` attribute of this item at `now`. Tagged enums use their variant's
+    #[must_use]
+    fn item_updated_at(&self, now: SystemTime) -> Option<(&'static str, AttributeValue)> {
+        Self::updated_at(now)
+    }
+}
+
+/// Plain attribute maps, so they can be `#[nitro(flatten)]`ed
+impl Timestamped for crate::Attributes {}
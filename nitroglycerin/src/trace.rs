@@ -0,0 +1,94 @@
+use std::{error::Error, future::Future};
+
+#[cfg(feature = "tracing")]
+use crate::meta::Meta;
+use crate::{error::Classify, operation::Request, DynamoError};
+
+/// Results of an `execute_with_meta` call
+#[cfg(feature = "tracing")]
+pub trait WithMeta {
+    fn meta(&self) -> &Meta;
+}
+
+#[cfg(feature = "tracing")]
+impl WithMeta for Meta {
+    fn meta(&self) -> &Meta {
+        self
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<T> WithMeta for (T, Meta) {
+    fn meta(&self) -> &Meta {
+        &self.1
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<T, U> WithMeta for (T, U, Meta) {
+    fn meta(&self) -> &Meta {
+        &self.2
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub type Span = tracing::Span;
+#[cfg(not(feature = "tracing"))]
+pub struct Span;
+
+/// Create the span an `execute` of the request runs in
+#[cfg(feature = "tracing")]
+pub fn span<I: Request>(input: &I) -> Span {
+    use tracing::field::Empty;
+    tracing::info_span!(
+        "dynamodb",
+        operation = I::OPERATION.as_str(),
+        table = input.table_name(),
+        index = input.index_name(),
+        key_condition = input.key_condition_expression(),
+        count = Empty,
+        scanned_count = Empty,
+        retries = Empty,
+        error = Empty,
+    )
+}
+
+/// Create the span an `execute` of the request runs in
+#[cfg(not(feature = "tracing"))]
+pub const fn span<I: Request>(_input: &I) -> Span {
+    Span
+}
+
+/// Run the `execute` future inside the span, recording its item counts or error kind
+#[cfg(feature = "tracing")]
+pub async fn instrument<T: WithMeta, E: Error + Classify + 'static>(span: Span, f: impl Future<Output = Result<T, DynamoError<E>>>) -> Result<T, DynamoError<E>> {
+    use tracing::Instrument;
+    let result = f.instrument(span.clone()).await;
+    match &result {
+        Ok(output) => {
+            let meta = output.meta();
+            span.record("count", meta.count);
+            span.record("scanned_count", meta.scanned_count);
+        }
+        Err(err) => {
+            span.record("error", err.kind().as_str());
+        }
+    }
+    result
+}
+
+/// Run the `execute` future inside the span, recording its item counts or error kind
+#[cfg(not(feature = "tracing"))]
+pub async fn instrument<T, E: Error + Classify + 'static>(_span: Span, f: impl Future<Output = Result<T, DynamoError<E>>>) -> Result<T, DynamoError<E>> {
+    f.await
+}
+
+/// Record the number of retries made by the request in the current span
+#[cfg(feature = "tracing")]
+pub fn record_retries(retries: u32) {
+    tracing::Span::current().record("retries", retries);
+}
+
+/// Record the number of retries made by the request in the current span
+#[cfg(not(feature = "tracing"))]
+pub const fn record_retries(_retries: u32) {}
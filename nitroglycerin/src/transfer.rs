@@ -0,0 +1,252 @@
+use std::{
+    convert::TryFrom,
+    io::{self, BufRead, Write},
+};
+
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::{BatchWriteItemError, BatchWriteItemInput, PutRequest, ScanError, ScanInput, WriteRequest};
+use thiserror::Error;
+
+use crate::{
+    client::DynamoDb,
+    error::{self, Classify, ErrorKind},
+    json::{self, PlainJson},
+    retry::RetryPolicy,
+    AttributeError, Attributes, Table,
+};
+
+/// Dynamodb allows at most 25 put requests per batch write
+const BATCH_SIZE: usize = 25;
+
+/// Error returned when exporting or importing a table
+#[derive(Debug, Error)]
+pub enum TransferError {
+    /// Error occured while reading or writing lines
+    #[error("could not transfer items: {0}")]
+    Io(#[from] io::Error),
+
+    /// Error originated from a scan request
+    #[error("could not scan table: {0}")]
+    Scan(#[from] RusotoError<ScanError>),
+
+    /// Error originated from a batch write request
+    #[error("could not write items: {0}")]
+    BatchWrite(#[from] RusotoError<BatchWriteItemError>),
+
+    /// Error occurs when a scanned item could not be parsed into the exported type
+    #[error("could not parse item {}: {source}", error::display_key(.item))]
+    InvalidItem {
+        /// The item that could not be parsed
+        item: Attributes,
+        /// The parse error
+        source: AttributeError,
+    },
+
+    /// Error occurs when a line of the imported file is not an item in the expected format
+    #[error("could not parse line {line}: {source}")]
+    InvalidLine {
+        /// The line number, starting from 1
+        line: usize,
+        /// The parse error
+        source: AttributeError,
+    },
+
+    /// Error occurs when dynamodb keeps returning unprocessed items once retries run out
+    #[error("{0} items were left unprocessed")]
+    Unprocessed(usize),
+}
+
+impl Classify for TransferError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io(_) => ErrorKind::Network,
+            Self::Scan(err) => err.kind(),
+            Self::BatchWrite(err) => err.kind(),
+            Self::InvalidItem { .. } | Self::InvalidLine { .. } => ErrorKind::Parse,
+            Self::Unprocessed(_) => ErrorKind::Throttled,
+        }
+    }
+}
+
+/// Json format of each line of an exported table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The typed dynamodb json wire format, which round trips exactly
+    Dynamo,
+    /// Plain json, converted with the given options
+    Plain(PlainJson),
+}
+
+impl Format {
+    /// Format the item as a single line of json
+    #[must_use]
+    pub fn write_item(self, item: &Attributes) -> String {
+        match self {
+            Self::Dynamo => json::to_string(item),
+            Self::Plain(options) => options.item_to_value(item).to_string(),
+        }
+    }
+
+    /// Parse an item from json
+    ///
+    /// # Errors
+    /// Will error if the string is not json, or not an item in this format
+    pub fn read_item(self, line: &str) -> Result<Attributes, AttributeError> {
+        match self {
+            Self::Dynamo => json::from_str(line),
+            Self::Plain(options) => {
+                let value = serde_json::from_str(line).map_err(|err| AttributeError::ParseError(Box::new(err)))?;
+                options.item_from_value(value)
+            }
+        }
+    }
+}
+
+/// Exports tables to, and imports them from, json lines with one item per line
+///
+/// Scans and batch writes are retried according to the [`RetryPolicy`], as are
+/// batch writes that leave items unprocessed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    format: Format,
+    retry: RetryPolicy,
+}
+
+impl Default for Transfer {
+    fn default() -> Self {
+        Self {
+            format: Format::Dynamo,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl Transfer {
+    /// Json format of each line
+    #[must_use]
+    pub const fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// How failed requests and unprocessed items are retried
+    #[must_use]
+    pub const fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Scan every item of the table and write it as a line
+    ///
+    /// Returns the number of items written
+    ///
+    /// # Errors
+    /// Will error if a scan request fails or a line could not be written
+    pub async fn export<D, W>(&self, client: &D, table_name: &str, writer: W) -> Result<usize, TransferError>
+    where
+        D: DynamoDb + Sync + ?Sized,
+        W: Write + Send,
+    {
+        self.export_with(client, table_name, writer, Ok).await
+    }
+
+    /// Scan every item of the table, check that it parses as `T`, and write it as a line
+    ///
+    /// Returns the number of items written
+    ///
+    /// # Errors
+    /// Will error if a scan request fails, an item could not be parsed or a line could not be written
+    pub async fn export_typed<T, D, W>(&self, client: &D, writer: W) -> Result<usize, TransferError>
+    where
+        T: Table + TryFrom<Attributes, Error = AttributeError> + Into<Attributes>,
+        D: DynamoDb + Sync + ?Sized,
+        W: Write + Send,
+    {
+        let table_name = T::table_name();
+        self.export_with(client, &table_name, writer, |item| T::try_from(item.clone()).map(Into::into).map_err(|source| (item, source)))
+            .await
+    }
+
+    async fn export_with<D, W, F>(&self, client: &D, table_name: &str, mut writer: W, convert: F) -> Result<usize, TransferError>
+    where
+        D: DynamoDb + Sync + ?Sized,
+        W: Write + Send,
+        F: Fn(Attributes) -> Result<Attributes, (Attributes, AttributeError)> + Send,
+    {
+        let mut count = 0;
+        let mut exclusive_start_key = None;
+        loop {
+            let input = ScanInput {
+                table_name: table_name.to_owned(),
+                exclusive_start_key,
+                ..ScanInput::default()
+            };
+            let output = self.retry.run(|| client.scan(input.clone())).await.map_err(|err| err.error)?;
+
+            for item in output.items.unwrap_or_default() {
+                let item = convert(item).map_err(|(item, source)| TransferError::InvalidItem { item, source })?;
+                writeln!(writer, "{}", self.format.write_item(&item))?;
+                count += 1;
+            }
+
+            exclusive_start_key = output.last_evaluated_key;
+            if exclusive_start_key.is_none() {
+                writer.flush()?;
+                return Ok(count);
+            }
+        }
+    }
+
+    /// Read every line as an item and batch write them into the table
+    ///
+    /// Blank lines are ignored. Returns the number of items written
+    ///
+    /// # Errors
+    /// Will error if a line could not be read or parsed, or a batch write fails
+    pub async fn import<D, R>(&self, client: &D, table_name: &str, reader: R) -> Result<usize, TransferError>
+    where
+        D: DynamoDb + Sync + ?Sized,
+        R: BufRead + Send,
+    {
+        let mut count = 0;
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let item = self.format.read_item(&line).map_err(|source| TransferError::InvalidLine { line: i + 1, source })?;
+            batch.push(WriteRequest {
+                put_request: Some(PutRequest { item }),
+                ..WriteRequest::default()
+            });
+            if batch.len() == BATCH_SIZE {
+                count += batch.len();
+                self.batch_write(client, table_name, std::mem::take(&mut batch)).await?;
+            }
+        }
+        if !batch.is_empty() {
+            count += batch.len();
+            self.batch_write(client, table_name, batch).await?;
+        }
+        Ok(count)
+    }
+
+    async fn batch_write<D>(&self, client: &D, table_name: &str, requests: Vec<WriteRequest>) -> Result<(), TransferError>
+    where
+        D: DynamoDb + Sync + ?Sized,
+    {
+        let input = BatchWriteItemInput {
+            request_items: std::iter::once((table_name.to_owned(), requests)).collect(),
+            ..BatchWriteItemInput::default()
+        };
+        let (output, _) = self.retry.send(input, |input| client.batch_write_item(input)).await;
+
+        let unprocessed = output?.unprocessed_items.and_then(|mut items| items.remove(table_name)).unwrap_or_default();
+        if unprocessed.is_empty() {
+            Ok(())
+        } else {
+            Err(TransferError::Unprocessed(unprocessed.len()))
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    convert::TryFrom,
+    marker::PhantomData,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rusoto_dynamodb::{AttributeValue, TimeToLiveSpecification, UpdateTimeToLiveError, UpdateTimeToLiveInput};
+
+use crate::{
+    convert::{is_null, null, FromAttributeValue, IntoAttributeValue},
+    AttributeError, DynamoDb, DynamoError, Table,
+};
+
+/// Types that can be stored in a `#[nitro(ttl)]` attribute
+///
+/// Dynamodb only expires items whose ttl attribute is a number of seconds since the unix epoch,
+/// so every implementation is stored that way regardless of the usual conversion of the type
+pub trait Expiry: Sized {
+    /// Convert into seconds since the unix epoch
+    fn into_epoch_seconds(self) -> i64;
+    /// Convert from seconds since the unix epoch
+    ///
+    /// # Errors
+    /// Will error if the seconds are out of range for the type
+    fn from_epoch_seconds(seconds: i64) -> Result<Self, AttributeError>;
+
+    /// Convert into an [`AttributeValue`] as seconds
+    fn into_ttl_av(self) -> AttributeValue {
+        self.into_epoch_seconds().into_av()
+    }
+    /// Convert from an [`AttributeValue`] as seconds
+    ///
+    /// # Errors
+    /// Will error if the attribute value is not a number, or is out of range for the type
+    fn try_from_ttl_av(av: AttributeValue) -> Result<Self, AttributeError> {
+        Self::from_epoch_seconds(i64::try_from_av(av)?)
+    }
+}
+
+fn out_of_range(seconds: i64) -> AttributeError {
+    AttributeError::ParseError(format!("{seconds} seconds since the unix epoch is out of range").into())
+}
+
+impl Expiry for i64 {
+    fn into_epoch_seconds(self) -> i64 {
+        self
+    }
+    fn from_epoch_seconds(seconds: i64) -> Result<Self, AttributeError> {
+        Ok(seconds)
+    }
+}
+
+impl Expiry for u64 {
+    fn into_epoch_seconds(self) -> i64 {
+        i64::try_from(self).unwrap_or(i64::MAX)
+    }
+    fn from_epoch_seconds(seconds: i64) -> Result<Self, AttributeError> {
+        Self::try_from(seconds).map_err(|_| out_of_range(seconds))
+    }
+}
+
+impl Expiry for SystemTime {
+    fn into_epoch_seconds(self) -> i64 {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(d) => i64::try_from(d.as_secs()).unwrap_or(i64::MAX),
+            Err(e) => -i64::try_from(e.duration().as_secs()).unwrap_or(i64::MAX),
+        }
+    }
+    fn from_epoch_seconds(seconds: i64) -> Result<Self, AttributeError> {
+        let offset = Duration::from_secs(seconds.unsigned_abs());
+        let time = if seconds < 0 { UNIX_EPOCH.checked_sub(offset) } else { UNIX_EPOCH.checked_add(offset) };
+        time.ok_or_else(|| out_of_range(seconds))
+    }
+}
+
+/// A time to live relative to now. Items read back hold the time remaining, or zero if they have already expired
+impl Expiry for Duration {
+    fn into_epoch_seconds(self) -> i64 {
+        SystemTime::now().checked_add(self).map_or(i64::MAX, SystemTime::into_epoch_seconds)
+    }
+    fn from_epoch_seconds(seconds: i64) -> Result<Self, AttributeError> {
+        Ok(SystemTime::from_epoch_seconds(seconds)?.duration_since(SystemTime::now()).unwrap_or_default())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Expiry for chrono::DateTime<chrono::Utc> {
+    fn into_epoch_seconds(self) -> i64 {
+        self.timestamp()
+    }
+    fn from_epoch_seconds(seconds: i64) -> Result<Self, AttributeError> {
+        use chrono::TimeZone;
+
+        chrono::Utc.timestamp_opt(seconds, 0).single().ok_or_else(|| out_of_range(seconds))
+    }
+}
+
+/// Items without an expiry are stored as null, so they never expire
+impl<T: Expiry> Expiry for Option<T> {
+    fn into_epoch_seconds(self) -> i64 {
+        self.map_or(0, T::into_epoch_seconds)
+    }
+    fn from_epoch_seconds(seconds: i64) -> Result<Self, AttributeError> {
+        T::from_epoch_seconds(seconds).map(Some)
+    }
+
+    fn into_ttl_av(self) -> AttributeValue {
+        self.map_or_else(null, T::into_ttl_av)
+    }
+    fn try_from_ttl_av(av: AttributeValue) -> Result<Self, AttributeError> {
+        if is_null(&av) {
+            Ok(None)
+        } else {
+            T::try_from_ttl_av(av).map(Some)
+        }
+    }
+}
+
+/// Convert an [`Expiry`] into an [`AttributeValue`] as seconds
+pub fn into_av<T: Expiry>(t: T) -> AttributeValue {
+    t.into_ttl_av()
+}
+
+/// Convert an [`AttributeValue`] as seconds into an [`Expiry`]
+///
+/// # Errors
+/// Will error if the attribute value is not a number, or is out of range for the type
+pub fn try_from_av<T: Expiry>(av: AttributeValue) -> Result<T, AttributeError> {
+    T::try_from_ttl_av(av)
+}
+
+/// Trait that declares the time to live attribute of a table. Implemented by the `Attributes` derive
+/// for types with a `#[nitro(ttl)]` field
+pub trait TimeToLive {
+    /// Name of the `#[nitro(ttl)]` attribute
+    fn ttl_attribute() -> &'static str;
+}
+
+/// Builder that enables time to live on a table
+pub struct Expr<'d, D: 'd + ?Sized, T> {
+    client: &'d D,
+    input: UpdateTimeToLiveInput,
+    _phantom: PhantomData<T>,
+}
+
+impl<'d, D: 'd + ?Sized, T: Table + TimeToLive> Expr<'d, D, T> {
+    /// Create a new `Expr`
+    pub fn new(client: &'d D, enabled: bool) -> Self {
+        let input = UpdateTimeToLiveInput {
+            table_name: T::table_name(),
+            time_to_live_specification: TimeToLiveSpecification {
+                attribute_name: T::ttl_attribute().to_owned(),
+                enabled,
+            },
+        };
+        Self { client, input, _phantom: PhantomData }
+    }
+}
+
+impl<'d, D: 'd + ?Sized, T> Expr<'d, D, T>
+where
+    D: DynamoDb,
+    &'d D: Send,
+    T: Send,
+{
+    /// Execute the update time to live request
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails
+    pub async fn execute(self) -> Result<(), DynamoError<UpdateTimeToLiveError>> {
+        let Self { client, input, _phantom } = self;
+        client.update_time_to_live(input).await?;
+        Ok(())
+    }
+}
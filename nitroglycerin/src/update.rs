@@ -0,0 +1,298 @@
+use std::{collections::HashMap, convert::TryFrom, marker::PhantomData, time::SystemTime};
+
+use rusoto_dynamodb::{AttributeValue, UpdateItemError, UpdateItemInput, UpdateItemOutput};
+
+use crate::{
+    convert::{FromAttributeValue, IntoAttributeValue},
+    key,
+    meta::{ConsumedCapacityInput, Meta, ReturnConsumedCapacity},
+    trace,
+    timestamp::Timestamped,
+    version::Versioned,
+    AttributeError, Attributes, DynamoDb, DynamoError, Table,
+};
+
+/// Trait that declares a type can be built into an atomic counter update request
+pub trait Increment<'d, D: 'd + ?Sized>: Table {
+    /// The builder type that performs the update item request
+    type Builder;
+    /// Create the increment builder
+    fn increment(client: &'d D) -> Self::Builder;
+}
+
+impl<'d, D: 'd + ?Sized, K: key::Builder<'d, D, UpdateItemInput>> Increment<'d, D> for K {
+    type Builder = K::Builder;
+    fn increment(client: &'d D) -> Self::Builder {
+        K::key(client)
+    }
+}
+
+impl From<key::Key> for UpdateItemInput {
+    fn from(k: key::Key) -> Self {
+        let key::Key { table_name, key } = k;
+        Self { key, table_name, ..Self::default() }
+    }
+}
+
+/// Update expression builder, keeping track of the placeholder names and values used
+#[derive(Default)]
+pub(crate) struct Expression {
+    set: Vec<String>,
+    add: Vec<String>,
+    names: HashMap<String, String>,
+    values: HashMap<String, AttributeValue>,
+}
+
+impl Expression {
+    fn placeholders(&mut self, name: &str, value: AttributeValue) -> (String, String) {
+        let i = self.names.len();
+        let (n, v) = (format!("#{i}"), format!(":{i}"));
+        self.names.insert(n.clone(), name.to_owned());
+        self.values.insert(v.clone(), value);
+        (n, v)
+    }
+
+    /// `SET name = value`
+    pub fn set(&mut self, name: &str, value: AttributeValue) {
+        let (n, v) = self.placeholders(name, value);
+        self.set.push(format!("{n} = {v}"));
+    }
+
+    /// `SET name = if_not_exists(name, value)`
+    pub fn set_if_not_exists(&mut self, name: &str, value: AttributeValue) {
+        let (n, v) = self.placeholders(name, value);
+        self.set.push(format!("{n} = if_not_exists({n}, {v})"));
+    }
+
+    /// `ADD name value`
+    pub fn add(&mut self, name: &str, value: AttributeValue) {
+        let (n, v) = self.placeholders(name, value);
+        self.add.push(format!("{n} {v}"));
+    }
+
+    /// Bump the version and fill in the timestamps of `T`, like [`put`](crate::DynamoDb::put) would
+    pub fn touch<T: Versioned + Timestamped>(&mut self, now: SystemTime) {
+        if let Some(name) = T::version_attribute() {
+            self.add(name, 1.into_av());
+        }
+        if let Some((name, value)) = T::created_at(now) {
+            self.set_if_not_exists(name, value);
+        }
+        if let Some((name, value)) = T::updated_at(now) {
+            self.set(name, value);
+        }
+    }
+
+    pub fn apply(self, input: &mut UpdateItemInput) {
+        let Self { set, add, names, values } = self;
+        let mut clauses = vec![];
+        if !set.is_empty() {
+            clauses.push(format!("SET {}", set.join(", ")));
+        }
+        if !add.is_empty() {
+            clauses.push(format!("ADD {}", add.join(", ")));
+        }
+        input.update_expression = Some(clauses.join(" "));
+        input.expression_attribute_names = Some(names);
+        input.expression_attribute_values = Some(values);
+    }
+}
+
+/// Final output of an increment builder chain
+pub struct Counter<'d, D: 'd + ?Sized, T, N> {
+    client: &'d D,
+    input: UpdateItemInput,
+    name: String,
+    _phantom: PhantomData<(T, N)>,
+}
+
+impl<'d, D: 'd + ?Sized, T: Versioned + Timestamped, N: IntoAttributeValue> Counter<'d, D, T, N> {
+    /// Atomically add `by` to the counter attribute `name` of the item identified by `expr`
+    pub fn new(expr: key::Expr<'d, D, UpdateItemInput, T>, name: &str, by: N) -> Self {
+        let key::Expr { client, mut input, .. } = expr;
+
+        let mut expression = Expression::default();
+        expression.add(name, by.into_av());
+        expression.touch::<T>(SystemTime::now());
+        expression.apply(&mut input);
+        input.return_values = Some("UPDATED_NEW".to_owned());
+
+        Self {
+            client,
+            input,
+            name: name.to_owned(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'d, D: 'd + ?Sized, T, N> Counter<'d, D, T, N> {
+    /// Report the capacity consumed by the request in the [`Meta`] returned by `execute_with_meta`
+    #[must_use]
+    pub fn return_consumed_capacity(mut self, return_consumed_capacity: ReturnConsumedCapacity) -> Self {
+        self.input.set_return_consumed_capacity(return_consumed_capacity);
+        self
+    }
+
+    /// Report the size of the affected item collection in the [`Meta`] returned by `execute_with_meta`
+    #[must_use]
+    pub fn return_item_collection_metrics(mut self) -> Self {
+        self.input.return_item_collection_metrics = Some("SIZE".to_owned());
+        self
+    }
+
+    fn return_values(self, return_values: &str) -> (&'d D, UpdateItemInput) {
+        let Self { client, mut input, .. } = self;
+        input.return_values = Some(return_values.to_owned());
+        (client, input)
+    }
+
+    /// Return the whole item as it was before the update, if it existed
+    #[must_use]
+    pub fn return_all_old(self) -> ReturnOld<'d, D, T> {
+        let (client, input) = self.return_values("ALL_OLD");
+        ReturnOld { client, input, _phantom: PhantomData }
+    }
+
+    /// Return the whole item as it is after the update
+    #[must_use]
+    pub fn return_all_new(self) -> ReturnNew<'d, D, T> {
+        let (client, input) = self.return_values("ALL_NEW");
+        ReturnNew { client, input, _phantom: PhantomData }
+    }
+
+    /// Return only the updated attributes as they were before the update, parsed into the projection `P`
+    #[must_use]
+    pub fn return_updated_old<P>(self) -> ReturnOld<'d, D, P> {
+        let (client, input) = self.return_values("UPDATED_OLD");
+        ReturnOld { client, input, _phantom: PhantomData }
+    }
+
+    /// Return only the updated attributes as they are after the update, parsed into the projection `P`
+    #[must_use]
+    pub fn return_updated_new<P>(self) -> ReturnNew<'d, D, P> {
+        let (client, input) = self.return_values("UPDATED_NEW");
+        ReturnNew { client, input, _phantom: PhantomData }
+    }
+}
+
+impl<'d, D: 'd + ?Sized, T, N> Counter<'d, D, T, N>
+where
+    D: DynamoDb,
+    T: Send,
+    &'d D: Send,
+    N: FromAttributeValue + Send,
+{
+    /// Execute the update item request, returning the new value of the counter
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails or the new value could not be parsed
+    pub async fn execute(self) -> Result<N, DynamoError<UpdateItemError>> {
+        Ok(self.execute_with_meta().await?.0)
+    }
+
+    /// Execute the update item request, returning the new value of the counter along with the consumed capacity
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails or the new value could not be parsed
+    pub async fn execute_with_meta(self) -> Result<(N, Meta), DynamoError<UpdateItemError>> {
+        let Self { client, input, name, _phantom } = self;
+        let span = trace::span(&input);
+        let (table_name, key) = (input.table_name.clone(), input.key.clone());
+        trace::instrument(span, async move {
+            let mut output = client.update_item(input).await?;
+            let meta = meta(&mut output);
+            let mut attributes = output.attributes.ok_or(AttributeError::MissingAttributes)?;
+            let Some(value) = attributes.remove(&name) else { return Err(AttributeError::MissingField(name).into()) };
+            let value = N::try_from_av(value).map_err(|e| e.in_field(&name)).map_err(DynamoError::item(&table_name, key))?;
+            Ok((value, meta))
+        })
+        .await
+    }
+}
+
+/// Update item builder which will return the attributes as they were before the update
+pub struct ReturnOld<'d, D: 'd + ?Sized, O> {
+    client: &'d D,
+    input: UpdateItemInput,
+    _phantom: PhantomData<O>,
+}
+
+impl<'d, D: 'd + ?Sized, O> ReturnOld<'d, D, O>
+where
+    D: DynamoDb,
+    &'d D: Send,
+    O: TryFrom<Attributes, Error = AttributeError> + Send,
+{
+    /// Execute the update item request, returning the old attributes if there were any
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails or the returned attributes could not be parsed
+    pub async fn execute(self) -> Result<Option<O>, DynamoError<UpdateItemError>> {
+        Ok(self.execute_with_meta().await?.0)
+    }
+
+    /// Execute the update item request, returning the old attributes if there were any, along with the consumed capacity
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails or the returned attributes could not be parsed
+    pub async fn execute_with_meta(self) -> Result<(Option<O>, Meta), DynamoError<UpdateItemError>> {
+        let Self { client, input, _phantom } = self;
+        let span = trace::span(&input);
+        let (table_name, key) = (input.table_name.clone(), input.key.clone());
+        trace::instrument(span, async move {
+            let mut output = client.update_item(input).await?;
+            let meta = meta(&mut output);
+            let item = output.attributes.map(O::try_from).transpose().map_err(DynamoError::item(&table_name, key))?;
+            Ok((item, meta))
+        })
+        .await
+    }
+}
+
+/// Update item builder which will return the attributes as they are after the update
+pub struct ReturnNew<'d, D: 'd + ?Sized, O> {
+    client: &'d D,
+    input: UpdateItemInput,
+    _phantom: PhantomData<O>,
+}
+
+impl<'d, D: 'd + ?Sized, O> ReturnNew<'d, D, O>
+where
+    D: DynamoDb,
+    &'d D: Send,
+    O: TryFrom<Attributes, Error = AttributeError> + Send,
+{
+    /// Execute the update item request, returning the new attributes
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails or the returned attributes could not be parsed
+    pub async fn execute(self) -> Result<O, DynamoError<UpdateItemError>> {
+        Ok(self.execute_with_meta().await?.0)
+    }
+
+    /// Execute the update item request, returning the new attributes along with the consumed capacity
+    ///
+    /// # Errors
+    /// Will error if the dynamodb request fails or the returned attributes could not be parsed
+    pub async fn execute_with_meta(self) -> Result<(O, Meta), DynamoError<UpdateItemError>> {
+        let Self { client, input, _phantom } = self;
+        let span = trace::span(&input);
+        let (table_name, key) = (input.table_name.clone(), input.key.clone());
+        trace::instrument(span, async move {
+            let mut output = client.update_item(input).await?;
+            let meta = meta(&mut output);
+            let attributes = output.attributes.ok_or(AttributeError::MissingAttributes)?;
+            Ok((O::try_from(attributes).map_err(DynamoError::item(&table_name, key))?, meta))
+        })
+        .await
+    }
+}
+
+fn meta(output: &mut UpdateItemOutput) -> Meta {
+    Meta {
+        consumed_capacity: output.consumed_capacity.take(),
+        item_collection_metrics: output.item_collection_metrics.take(),
+        ..Meta::default()
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use crate::{convert::IntoAttributeValue, Attributes};
+
+/// Trait that describes the optimistic locking version of an item. Implemented by the `Attributes` derive
+///
+/// The version is checked by [`put`](crate::DynamoDb::put), and by delete requests given an
+/// [`if_version`](crate::key::Expr::if_version) condition
+pub trait Versioned {
+    /// Name of the `#[nitro(version)]` attribute, or `None` if the type is not versioned
+    ///
+    /// Tagged enums return `None`, as only their variants know the name
+    #[must_use]
+    fn version_attribute() -> Option<&'static str> {
+        None
+    }
+
+    /// Name of the `#[nitro(version)]` attribute of this item, or `None` if it is not versioned
+    #[must_use]
+    fn item_version_attribute(&self) -> Option<&'static str> {
+        Self::version_attribute()
+    }
+
+    /// The version of the item as it was last read from dynamodb
+    #[must_use]
+    fn version(&self) -> u64 {
+        0
+    }
+
+    /// The version the item is written with next, or `None` if the version type has no room left for it
+    #[must_use]
+    fn next_version(&self) -> Option<u64> {
+        self.version().checked_add(1)
+    }
+}
+
+/// Trait that declares the version attribute of a table. Implemented by the `Attributes` derive
+/// for types with a `#[nitro(version)]` field
+pub trait HasVersion: Versioned {
+    /// Name of the `#[nitro(version)]` attribute
+    fn version_attribute_name() -> &'static str;
+}
+
+/// Condition expression that checks the stored version of an item
+pub(crate) struct Condition {
+    pub expression: String,
+    pub names: HashMap<String, String>,
+    pub values: Option<Attributes>,
+}
+
+impl Condition {
+    /// Check that the stored version equals `version`. Version 0 expects that the item does not exist yet
+    pub fn new(attribute: &str, version: u64) -> Self {
+        let names = <_>::into_iter([("#v".to_owned(), attribute.to_owned())]).collect();
+        if version == 0 {
+            Self {
+                expression: "attribute_not_exists(#v)".to_owned(),
+                names,
+                values: None,
+            }
+        } else {
+            Self {
+                expression: "#v = :v".to_owned(),
+                names,
+                values: Some(<_>::into_iter([(":v".to_owned(), version.into_av())]).collect()),
+            }
+        }
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    error::Error,
+    io::{self, Read, Write},
+    num::NonZeroUsize,
+};
+
+use clap::Args;
+use nitroglycerin::{
+    dynamodb::{AttributeValue, DeleteItemInput, DynamoDb, GetItemInput, PutItemInput, QueryInput, ScanInput},
+    json::PlainJson,
+    transfer::Format,
+    Attributes,
+};
+
+use crate::{key::KeySchema, JsonFormat};
+
+/// Expression attribute names and values shared by the item commands
+#[derive(Debug, Clone, Default, Args)]
+pub struct Expressions {
+    /// Expression attribute names, as a json object such as {"#n": "name"}
+    #[arg(long, value_parser = parse_names)]
+    names: Option<HashMap<String, String>>,
+
+    /// Expression attribute values, as a plain json object such as {":v": 1}
+    #[arg(long, value_parser = parse_values)]
+    values: Option<Attributes>,
+}
+
+fn parse_names(s: &str) -> Result<HashMap<String, String>, String> {
+    serde_json::from_str(s).map_err(|err| format!("expected a json object of strings: {err}"))
+}
+
+fn parse_values(s: &str) -> Result<Attributes, String> {
+    let value = serde_json::from_str(s).map_err(|err| err.to_string())?;
+    PlainJson::default().item_from_value(value).map_err(|err| err.to_string())
+}
+
+impl Expressions {
+    fn name(&mut self, placeholder: &str, name: &str) {
+        self.names.get_or_insert_with(HashMap::new).insert(placeholder.to_owned(), name.to_owned());
+    }
+
+    fn value(&mut self, placeholder: &str, value: AttributeValue) {
+        self.values.get_or_insert_with(Attributes::new).insert(placeholder.to_owned(), value);
+    }
+}
+
+/// Write each item to stdout as a json line
+fn print_items(format: Format, items: &[Attributes]) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    for item in items {
+        writeln!(stdout, "{}", format.write_item(item))?;
+    }
+    stdout.flush()
+}
+
+/// Get an item by its key and write it as a json line
+#[derive(Debug, Args)]
+pub struct Get {
+    /// Name of the table
+    table: String,
+
+    /// Value of the partition key
+    partition: String,
+
+    /// Value of the sort key, if the table has one
+    sort: Option<String>,
+
+    /// Projection expression of the attributes to get
+    #[arg(long)]
+    projection: Option<String>,
+
+    /// Read the item with strong consistency
+    #[arg(long)]
+    consistent: bool,
+
+    /// Expression attribute names of the projection, as a json object such as {"#n": "name"}
+    #[arg(long, value_parser = parse_names)]
+    names: Option<HashMap<String, String>>,
+
+    /// Json format of the output
+    #[arg(long, short, value_enum, default_value_t = JsonFormat::Plain)]
+    format: JsonFormat,
+}
+
+impl Get {
+    pub async fn run(self, client: &(impl DynamoDb + Sync)) -> Result<(), Box<dyn Error>> {
+        let schema = KeySchema::describe(client, &self.table, None).await?;
+        let input = GetItemInput {
+            key: schema.key(&self.partition, self.sort.as_deref())?,
+            table_name: self.table,
+            projection_expression: self.projection,
+            consistent_read: Some(self.consistent),
+            expression_attribute_names: self.names,
+            ..GetItemInput::default()
+        };
+        let item = client.get_item(input).await?.item.ok_or("item not found")?;
+        Ok(print_items(self.format.into(), &[item])?)
+    }
+}
+
+/// Put an item, replacing any item with the same key
+#[derive(Debug, Args)]
+pub struct Put {
+    /// Name of the table
+    table: String,
+
+    /// The item as a json object, read from stdin if missing or -
+    item: Option<String>,
+
+    /// Condition expression that must hold for the put to succeed
+    #[arg(long)]
+    condition: Option<String>,
+
+    #[command(flatten)]
+    expressions: Expressions,
+
+    /// Json format of the item
+    #[arg(long, short, value_enum, default_value_t = JsonFormat::Plain)]
+    format: JsonFormat,
+}
+
+impl Put {
+    pub async fn run(self, client: &(impl DynamoDb + Sync)) -> Result<(), Box<dyn Error>> {
+        let item = match self.item {
+            Some(item) if item != "-" => item,
+            _ => {
+                let mut item = String::new();
+                io::stdin().read_to_string(&mut item)?;
+                item
+            }
+        };
+        let format: Format = self.format.into();
+        let input = PutItemInput {
+            item: format.read_item(&item)?,
+            table_name: self.table,
+            condition_expression: self.condition,
+            expression_attribute_names: self.expressions.names,
+            expression_attribute_values: self.expressions.values,
+            ..PutItemInput::default()
+        };
+        client.put_item(input).await?;
+        Ok(())
+    }
+}
+
+/// Delete an item by its key and write the deleted item as a json line
+#[derive(Debug, Args)]
+pub struct Delete {
+    /// Name of the table
+    table: String,
+
+    /// Value of the partition key
+    partition: String,
+
+    /// Value of the sort key, if the table has one
+    sort: Option<String>,
+
+    /// Condition expression that must hold for the delete to succeed
+    #[arg(long)]
+    condition: Option<String>,
+
+    #[command(flatten)]
+    expressions: Expressions,
+
+    /// Json format of the output
+    #[arg(long, short, value_enum, default_value_t = JsonFormat::Plain)]
+    format: JsonFormat,
+}
+
+impl Delete {
+    pub async fn run(self, client: &(impl DynamoDb + Sync)) -> Result<(), Box<dyn Error>> {
+        let schema = KeySchema::describe(client, &self.table, None).await?;
+        let input = DeleteItemInput {
+            key: schema.key(&self.partition, self.sort.as_deref())?,
+            table_name: self.table,
+            condition_expression: self.condition,
+            expression_attribute_names: self.expressions.names,
+            expression_attribute_values: self.expressions.values,
+            return_values: Some("ALL_OLD".to_owned()),
+            ..DeleteItemInput::default()
+        };
+        let item = client.delete_item(input).await?.attributes.ok_or("item not found")?;
+        Ok(print_items(self.format.into(), &[item])?)
+    }
+}
+
+/// Options shared by the commands that read many items
+#[derive(Debug, Args)]
+pub struct Listing {
+    /// Name of a secondary index to read instead of the table
+    #[arg(long)]
+    index: Option<String>,
+
+    /// Filter expression applied to the items read
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Projection expression of the attributes to get
+    #[arg(long)]
+    projection: Option<String>,
+
+    /// Maximum number of items to read and write, defaults to every item
+    #[arg(long)]
+    limit: Option<NonZeroUsize>,
+
+    #[command(flatten)]
+    expressions: Expressions,
+
+    /// Json format of the output
+    #[arg(long, short, value_enum, default_value_t = JsonFormat::Plain)]
+    format: JsonFormat,
+}
+
+impl Listing {
+    /// Limit of the next page request, so no more items are read than are left to write
+    fn page_limit(&self, written: usize) -> Option<i64> {
+        self.limit.map(|limit| i64::try_from(limit.get().saturating_sub(written)).unwrap_or(i64::MAX))
+    }
+
+    /// Write a page of items, truncated to the limit. Returns whether the limit was reached
+    fn print_page(&self, written: &mut usize, mut items: Vec<Attributes>) -> io::Result<bool> {
+        if let Some(limit) = self.limit {
+            items.truncate(limit.get().saturating_sub(*written));
+        }
+        *written += items.len();
+        print_items(self.format.into(), &items)?;
+        Ok(self.limit.is_some_and(|limit| *written >= limit.get()))
+    }
+}
+
+/// Query the items of a partition and write them as json lines
+#[derive(Debug, Args)]
+pub struct Query {
+    /// Name of the table
+    table: String,
+
+    /// Value of the partition key
+    partition: String,
+
+    /// Condition on the sort key, as an operator followed by its values, one of
+    /// `=`, `<`, `<=`, `>`, `>=`, `begins_with` or `between`
+    #[arg(long, num_args = 2..=3, value_names = ["OP", "VALUE"])]
+    sort: Option<Vec<String>>,
+
+    /// Read the items in descending order of the sort key
+    #[arg(long)]
+    reverse: bool,
+
+    #[command(flatten)]
+    listing: Listing,
+}
+
+/// Build the key condition of a query, adding the names and values it uses to `expressions`
+///
+/// `sort` is an operator followed by its values, as given to `--sort`
+fn key_condition(schema: &KeySchema, partition: &str, sort: Option<&[String]>, expressions: &mut Expressions) -> Result<String, Box<dyn Error>> {
+    expressions.name("#pk", &schema.partition.name);
+    expressions.value(":pk", schema.partition.value(partition)?);
+    let Some(args) = sort else { return Ok("#pk = :pk".to_owned()) };
+
+    let sort = schema.sort.as_ref().ok_or("table has no sort key")?;
+    expressions.name("#sk", &sort.name);
+    let condition = match args {
+        [op, value] if ["=", "<", "<=", ">", ">="].contains(&op.as_str()) => {
+            expressions.value(":sk", sort.value(value)?);
+            format!("#sk {op} :sk")
+        }
+        [op, value] if op == "begins_with" => {
+            expressions.value(":sk", sort.value(value)?);
+            "begins_with(#sk, :sk)".to_owned()
+        }
+        [op, start, end] if op == "between" => {
+            expressions.value(":sk", sort.value(start)?);
+            expressions.value(":sk_end", sort.value(end)?);
+            "#sk BETWEEN :sk AND :sk_end".to_owned()
+        }
+        args => return Err(format!("invalid sort condition {}", args.join(" ")).into()),
+    };
+    Ok(format!("#pk = :pk AND {condition}"))
+}
+
+impl Query {
+    pub async fn run(mut self, client: &(impl DynamoDb + Sync)) -> Result<(), Box<dyn Error>> {
+        let schema = KeySchema::describe(client, &self.table, self.listing.index.as_deref()).await?;
+        let key_condition = key_condition(&schema, &self.partition, self.sort.as_deref(), &mut self.listing.expressions)?;
+
+        let mut written = 0;
+        let mut exclusive_start_key = None;
+        loop {
+            let input = QueryInput {
+                table_name: self.table.clone(),
+                index_name: self.listing.index.clone(),
+                key_condition_expression: Some(key_condition.clone()),
+                filter_expression: self.listing.filter.clone(),
+                projection_expression: self.listing.projection.clone(),
+                expression_attribute_names: self.listing.expressions.names.clone(),
+                expression_attribute_values: self.listing.expressions.values.clone(),
+                scan_index_forward: Some(!self.reverse),
+                limit: self.listing.page_limit(written),
+                exclusive_start_key,
+                ..QueryInput::default()
+            };
+            let output = client.query(input).await?;
+            let done = self.listing.print_page(&mut written, output.items.unwrap_or_default())?;
+
+            exclusive_start_key = output.last_evaluated_key;
+            if done || exclusive_start_key.is_none() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Scan every item of a table and write them as json lines
+#[derive(Debug, Args)]
+pub struct Scan {
+    /// Name of the table
+    table: String,
+
+    #[command(flatten)]
+    listing: Listing,
+}
+
+impl Scan {
+    pub async fn run(self, client: &(impl DynamoDb + Sync)) -> Result<(), Box<dyn Error>> {
+        let mut written = 0;
+        let mut exclusive_start_key = None;
+        loop {
+            let input = ScanInput {
+                table_name: self.table.clone(),
+                index_name: self.listing.index.clone(),
+                filter_expression: self.listing.filter.clone(),
+                projection_expression: self.listing.projection.clone(),
+                expression_attribute_names: self.listing.expressions.names.clone(),
+                expression_attribute_values: self.listing.expressions.values.clone(),
+                limit: self.listing.page_limit(written),
+                exclusive_start_key,
+                ..ScanInput::default()
+            };
+            let output = client.scan(input).await?;
+            let done = self.listing.print_page(&mut written, output.items.unwrap_or_default())?;
+
+            exclusive_start_key = output.last_evaluated_key;
+            if done || exclusive_start_key.is_none() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::tests::schema;
+
+    fn parse(sort: bool, args: &[&str]) -> Result<(String, Expressions), Box<dyn Error>> {
+        let args: Vec<_> = args.iter().map(|&arg| arg.to_owned()).collect();
+        let mut expressions = Expressions::default();
+        let condition = key_condition(&schema(sort), "acc_1", (!args.is_empty()).then_some(args.as_slice()), &mut expressions)?;
+        Ok((condition, expressions))
+    }
+
+    #[test]
+    fn key_condition_operators() {
+        let (condition, expressions) = parse(true, &[]).unwrap();
+        assert_eq!(condition, "#pk = :pk");
+        assert_eq!(expressions.names.unwrap().len(), 1);
+
+        let (condition, _) = parse(true, &["<=", "3"]).unwrap();
+        assert_eq!(condition, "#pk = :pk AND #sk <= :sk");
+
+        let (condition, _) = parse(true, &["begins_with", "3"]).unwrap();
+        assert_eq!(condition, "#pk = :pk AND begins_with(#sk, :sk)");
+
+        let (condition, expressions) = parse(true, &["between", "3", "5"]).unwrap();
+        assert_eq!(condition, "#pk = :pk AND #sk BETWEEN :sk AND :sk_end");
+        let values = expressions.values.unwrap();
+        assert_eq!(values[":sk"].n.as_deref(), Some("3"));
+        assert_eq!(values[":sk_end"].n.as_deref(), Some("5"));
+        assert_eq!(expressions.names.unwrap()["#sk"], "version");
+    }
+
+    #[test]
+    fn key_condition_errors() {
+        let err = parse(true, &["between", "3"]).unwrap_err();
+        assert_eq!(err.to_string(), "invalid sort condition between 3");
+
+        let err = parse(true, &["=", "3", "5"]).unwrap_err();
+        assert_eq!(err.to_string(), "invalid sort condition = 3 5");
+
+        let err = parse(true, &["!=", "3"]).unwrap_err();
+        assert_eq!(err.to_string(), "invalid sort condition != 3");
+
+        let err = parse(false, &["=", "3"]).unwrap_err();
+        assert_eq!(err.to_string(), "table has no sort key");
+    }
+
+    #[test]
+    fn page_limit() {
+        let listing = |limit| Listing {
+            index: None,
+            filter: None,
+            projection: None,
+            limit: NonZeroUsize::new(limit),
+            expressions: Expressions::default(),
+            format: JsonFormat::Plain,
+        };
+        assert_eq!(listing(0).page_limit(0), None);
+        assert_eq!(listing(10).page_limit(0), Some(10));
+        assert_eq!(listing(10).page_limit(7), Some(3));
+    }
+}
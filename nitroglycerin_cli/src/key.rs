@@ -0,0 +1,121 @@
+use std::error::Error;
+
+use nitroglycerin::{
+    dynamodb::{AttributeDefinition, AttributeValue, DescribeTableInput, DynamoDb, KeySchemaElement},
+    json, Attributes,
+};
+use serde_json::json;
+
+/// Name and type of a key attribute
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyAttribute {
+    pub name: String,
+    /// `S`, `N` or `B`
+    pub attribute_type: String,
+}
+
+impl KeyAttribute {
+    /// Convert a value given on the command line into an attribute value of the key type
+    pub fn value(&self, value: &str) -> Result<AttributeValue, Box<dyn Error>> {
+        json::from_value(json!({ &self.attribute_type: value })).map_err(|err| format!("invalid value {value:?} for key {}: {err}", self.name).into())
+    }
+}
+
+/// Key attributes of a table or one of its indexes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySchema {
+    pub partition: KeyAttribute,
+    pub sort: Option<KeyAttribute>,
+}
+
+impl KeySchema {
+    /// Look up the key schema of the table, or of the index if one is given
+    pub async fn describe(client: &(impl DynamoDb + Sync), table_name: &str, index_name: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let input = DescribeTableInput { table_name: table_name.to_owned() };
+        let table = client.describe_table(input).await?.table.ok_or("dynamodb did not describe the table")?;
+
+        let key_schema = match index_name {
+            None => table.key_schema,
+            Some(index_name) => {
+                let global = table.global_secondary_indexes.into_iter().flatten().map(|index| (index.index_name, index.key_schema));
+                let local = table.local_secondary_indexes.into_iter().flatten().map(|index| (index.index_name, index.key_schema));
+                let mut indexes = global.chain(local);
+                let index = indexes.find(|(name, _)| name.as_deref() == Some(index_name));
+                index.ok_or_else(|| format!("table {table_name} has no index {index_name}"))?.1
+            }
+        };
+
+        let definitions = table.attribute_definitions.unwrap_or_default();
+        let key_schema = key_schema.unwrap_or_default();
+        let attribute = |key_type: &str| -> Option<KeyAttribute> {
+            let KeySchemaElement { attribute_name, .. } = key_schema.iter().find(|key| key.key_type == key_type)?;
+            let AttributeDefinition { attribute_type, .. } = definitions.iter().find(|def| &def.attribute_name == attribute_name)?;
+            Some(KeyAttribute {
+                name: attribute_name.clone(),
+                attribute_type: attribute_type.clone(),
+            })
+        };
+
+        Ok(Self {
+            partition: attribute("HASH").ok_or("table has no partition key")?,
+            sort: attribute("RANGE"),
+        })
+    }
+
+    /// Build the key of an item from the values given on the command line
+    pub fn key(&self, partition: &str, sort: Option<&str>) -> Result<Attributes, Box<dyn Error>> {
+        let mut key = Attributes::new();
+        key.insert(self.partition.name.clone(), self.partition.value(partition)?);
+        match (&self.sort, sort) {
+            (Some(attribute), Some(sort)) => {
+                key.insert(attribute.name.clone(), attribute.value(sort)?);
+            }
+            (Some(attribute), None) => return Err(format!("missing value for sort key {}", attribute.name).into()),
+            (None, Some(_)) => return Err("table has no sort key".into()),
+            (None, None) => {}
+        }
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub fn schema(sort: bool) -> KeySchema {
+        let attribute = |name: &str, attribute_type: &str| KeyAttribute {
+            name: name.to_owned(),
+            attribute_type: attribute_type.to_owned(),
+        };
+        KeySchema {
+            partition: attribute("id", "S"),
+            sort: sort.then(|| attribute("version", "N")),
+        }
+    }
+
+    fn string(s: &str) -> AttributeValue {
+        AttributeValue { s: Some(s.to_owned()), ..AttributeValue::default() }
+    }
+
+    fn number(n: &str) -> AttributeValue {
+        AttributeValue { n: Some(n.to_owned()), ..AttributeValue::default() }
+    }
+
+    #[test]
+    fn key() {
+        let key = schema(true).key("acc_1", Some("3")).unwrap();
+        assert_eq!(key, vec![("id".to_owned(), string("acc_1")), ("version".to_owned(), number("3"))].into_iter().collect());
+
+        let key = schema(false).key("acc_1", None).unwrap();
+        assert_eq!(key, vec![("id".to_owned(), string("acc_1"))].into_iter().collect());
+    }
+
+    #[test]
+    fn key_errors() {
+        let err = schema(true).key("acc_1", None).unwrap_err();
+        assert_eq!(err.to_string(), "missing value for sort key version");
+
+        let err = schema(false).key("acc_1", Some("3")).unwrap_err();
+        assert_eq!(err.to_string(), "table has no sort key");
+    }
+}
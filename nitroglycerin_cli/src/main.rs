@@ -0,0 +1,161 @@
+//! `nitro`, a command line tool for dynamodb tables
+//!
+//! ```text
+//! nitro export Employees -o employees.jsonl
+//! nitro --endpoint http://localhost:8000 import Employees -i employees.jsonl
+//! nitro get Employees alice
+//! nitro query Employees engineering --index Teams --sort begins_with 2021 --limit 10
+//! nitro scan Employees --filter "age > :age" --values '{":age": 30}' -f dynamo
+//! nitro put Employees '{"id": "bob", "age": 42}' --condition "attribute_not_exists(id)"
+//! nitro delete Employees bob
+//! ```
+
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use nitroglycerin::{
+    dynamodb::DynamoDbClient,
+    json::PlainJson,
+    transfer::{Format, Transfer},
+};
+use rusoto_core::Region;
+
+mod item;
+mod key;
+
+#[derive(Debug, Parser)]
+#[command(name = "nitro", about = "Command line tools for dynamodb tables")]
+struct Cli {
+    #[command(flatten)]
+    connection: Connection,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Args)]
+struct Connection {
+    /// AWS region of the table, defaults to the region of the environment
+    #[arg(long, global = true, env = "AWS_REGION")]
+    region: Option<String>,
+
+    /// Custom endpoint url to send requests to, such as that of a local dynamodb
+    #[arg(long, global = true, env = "DYNAMODB_ENDPOINT")]
+    endpoint: Option<String>,
+}
+
+impl Connection {
+    fn client(&self) -> Result<DynamoDbClient, Box<dyn Error>> {
+        let region = match (&self.region, &self.endpoint) {
+            (region, Some(endpoint)) => Region::Custom {
+                name: region.clone().unwrap_or_else(|| Region::default().name().to_owned()),
+                endpoint: endpoint.clone(),
+            },
+            (Some(region), None) => Region::from_str(region)?,
+            (None, None) => Region::default(),
+        };
+        Ok(DynamoDbClient::new(region))
+    }
+}
+
+/// Json format of the items
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum JsonFormat {
+    /// The typed dynamodb json wire format, such as {"id": {"S": "x"}}
+    Dynamo,
+    /// Plain json, such as {"id": "x"}
+    Plain,
+}
+
+impl From<JsonFormat> for Format {
+    fn from(format: JsonFormat) -> Self {
+        match format {
+            JsonFormat::Dynamo => Self::Dynamo,
+            JsonFormat::Plain => Self::Plain(PlainJson::default()),
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Scan every item of a table and write it as a json line
+    Export {
+        /// Name of the table
+        table: String,
+
+        /// Json format of each line
+        #[arg(long, short, value_enum, default_value_t = JsonFormat::Dynamo)]
+        format: JsonFormat,
+
+        /// File to write the items to, defaults to stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Read json lines and batch write them into a table
+    Import {
+        /// Name of the table
+        table: String,
+
+        /// Json format of each line
+        #[arg(long, short, value_enum, default_value_t = JsonFormat::Dynamo)]
+        format: JsonFormat,
+
+        /// File to read the items from, defaults to stdin
+        #[arg(long, short)]
+        input: Option<PathBuf>,
+    },
+
+    Get(item::Get),
+    Put(item::Put),
+    Delete(item::Delete),
+    Query(item::Query),
+    Scan(item::Scan),
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run(Cli::parse()).await {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let client = cli.connection.client()?;
+
+    match cli.command {
+        Command::Export { table, format, output } => {
+            let writer: Box<dyn Write + Send> = match output {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(BufWriter::new(io::stdout())),
+            };
+            let count = Transfer::default().format(format.into()).export(&client, &table, writer).await?;
+            eprintln!("exported {count} items from {table}");
+        }
+        Command::Import { table, format, input } => {
+            let reader: Box<dyn BufRead + Send> = match input {
+                Some(path) => Box::new(BufReader::new(File::open(path)?)),
+                None => Box::new(BufReader::new(io::stdin())),
+            };
+            let count = Transfer::default().format(format.into()).import(&client, &table, reader).await?;
+            eprintln!("imported {count} items into {table}");
+        }
+        Command::Get(get) => get.run(&client).await?,
+        Command::Put(put) => put.run(&client).await?,
+        Command::Delete(delete) => delete.run(&client).await?,
+        Command::Query(query) => query.run(&client).await?,
+        Command::Scan(scan) => scan.run(&client).await?,
+    }
+
+    Ok(())
+}
@@ -3,6 +3,7 @@ use syn::{parse2, Token};
 
 use crate::iter::{Ext, SplitByState};
 
+pub mod container;
 pub mod field;
 
 fn equal<T: syn::parse::Parse>(tokens: TokenStream) -> syn::Result<T> {
@@ -23,6 +24,14 @@ fn equal<T: syn::parse::Parse>(tokens: TokenStream) -> syn::Result<T> {
     Ok(parse2::<Equal<_>>(tokens)?.t)
 }
 
+fn optional_equal<T: syn::parse::Parse>(tokens: TokenStream) -> syn::Result<Option<T>> {
+    if tokens.is_empty() {
+        Ok(None)
+    } else {
+        equal(tokens).map(Some)
+    }
+}
+
 fn empty(tokens: TokenStream) -> syn::Result<()> {
     struct Empty {}
     impl syn::parse::Parse for Empty {
@@ -0,0 +1,120 @@
+use proc_macro2::TokenStream;
+
+use super::{empty, equal, AttrBuilder};
+use crate::case::RenameRule;
+
+#[derive(Clone)]
+pub struct Attr {
+    pub transparent: Option<()>,
+    pub skip_if_none: Option<()>,
+    pub rename_all: Option<RenameRule>,
+    pub partition_key: Option<syn::LitStr>,
+    pub partition_key_name: Option<syn::LitStr>,
+    pub sort_key: Option<syn::LitStr>,
+    pub sort_key_name: Option<syn::LitStr>,
+    pub tag: Option<syn::LitStr>,
+    pub item: Option<syn::Type>,
+}
+
+impl Attr {
+    pub fn parse_attrs(attrs: Vec<syn::Attribute>) -> syn::Result<Self> {
+        Ok(Builder::default().parse_attrs(attrs)?.into())
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    transparent: Option<()>,
+    skip_if_none: Option<()>,
+    rename_all: Option<RenameRule>,
+    partition_key: Option<syn::LitStr>,
+    partition_key_name: Option<syn::LitStr>,
+    sort_key: Option<syn::LitStr>,
+    sort_key_name: Option<syn::LitStr>,
+    tag: Option<syn::LitStr>,
+    item: Option<syn::Type>,
+}
+
+impl Builder {
+    const fn transparent(&mut self, transparent: ()) -> &mut Self {
+        self.transparent = Some(transparent);
+        self
+    }
+    const fn skip_if_none(&mut self, skip_if_none: ()) -> &mut Self {
+        self.skip_if_none = Some(skip_if_none);
+        self
+    }
+    const fn rename_all(&mut self, rename_all: RenameRule) -> &mut Self {
+        self.rename_all = Some(rename_all);
+        self
+    }
+    fn partition_key(&mut self, partition_key: syn::LitStr) -> &mut Self {
+        self.partition_key = Some(partition_key);
+        self
+    }
+    fn partition_key_name(&mut self, partition_key_name: syn::LitStr) -> &mut Self {
+        self.partition_key_name = Some(partition_key_name);
+        self
+    }
+    fn sort_key(&mut self, sort_key: syn::LitStr) -> &mut Self {
+        self.sort_key = Some(sort_key);
+        self
+    }
+    fn sort_key_name(&mut self, sort_key_name: syn::LitStr) -> &mut Self {
+        self.sort_key_name = Some(sort_key_name);
+        self
+    }
+    fn tag(&mut self, tag: syn::LitStr) -> &mut Self {
+        self.tag = Some(tag);
+        self
+    }
+    fn item(&mut self, item: syn::Type) -> &mut Self {
+        self.item = Some(item);
+        self
+    }
+}
+
+impl From<Builder> for Attr {
+    fn from(value: Builder) -> Self {
+        let Builder {
+            transparent,
+            skip_if_none,
+            rename_all,
+            partition_key,
+            partition_key_name,
+            sort_key,
+            sort_key_name,
+            tag,
+            item,
+        } = value;
+        Self {
+            transparent,
+            skip_if_none,
+            rename_all,
+            partition_key,
+            partition_key_name,
+            sort_key,
+            sort_key_name,
+            tag,
+            item,
+        }
+    }
+}
+
+impl AttrBuilder for Builder {
+    fn parse(&mut self, ident: syn::Ident, tokens: TokenStream) -> syn::Result<()> {
+        match ident.to_string().as_ref() {
+            "transparent" => self.transparent(empty(tokens)?),
+            "skip_if_none" => self.skip_if_none(empty(tokens)?),
+            "rename_all" => self.rename_all(RenameRule::parse(&equal(tokens)?)?),
+            "partition_key" => self.partition_key(equal(tokens)?),
+            "partition_key_name" => self.partition_key_name(equal(tokens)?),
+            "sort_key" => self.sort_key(equal(tokens)?),
+            "sort_key_name" => self.sort_key_name(equal(tokens)?),
+            "tag" => self.tag(equal(tokens)?),
+            "item" => self.item(equal(tokens)?),
+            _ => return Err(syn::Error::new_spanned(ident, "unknown parameter")),
+        };
+        Ok(())
+    }
+}
@@ -1,8 +1,8 @@
-use std::convert::{Infallible, TryFrom, TryInto};
+use std::convert::{TryFrom, TryInto};
 
 use proc_macro2::{Span, TokenStream};
 
-use super::{empty, equal, AttrBuilder};
+use super::{empty, equal, optional_equal, AttrBuilder};
 
 #[derive(Clone)]
 pub struct Attr {
@@ -10,6 +10,23 @@ pub struct Attr {
     pub partition_key: Option<()>,
     pub sort_key: Option<()>,
     pub with: Option<syn::Path>,
+    pub skip: Option<()>,
+    pub default: Option<DefaultValue>,
+    pub skip_if_none: Option<()>,
+    pub flatten: Option<()>,
+    pub version: Option<()>,
+    pub created_at: Option<()>,
+    pub updated_at: Option<()>,
+    pub ttl: Option<()>,
+}
+
+/// How to fill in a field when its attribute is missing
+#[derive(Clone)]
+pub enum DefaultValue {
+    /// use the [`Default`] implementation of the field type
+    Trait,
+    /// call the given function
+    Path(syn::Path),
 }
 
 impl Attr {
@@ -24,6 +41,14 @@ struct Builder {
     partition_key: Option<()>,
     sort_key: Option<()>,
     with: Option<syn::Path>,
+    skip: Option<()>,
+    default: Option<DefaultValue>,
+    skip_if_none: Option<()>,
+    flatten: Option<()>,
+    version: Option<()>,
+    created_at: Option<()>,
+    updated_at: Option<()>,
+    ttl: Option<()>,
 }
 
 impl Builder {
@@ -31,11 +56,11 @@ impl Builder {
         self.rename = Some(rename);
         self
     }
-    fn partition_key(&mut self, partition_key: ()) -> &mut Self {
+    const fn partition_key(&mut self, partition_key: ()) -> &mut Self {
         self.partition_key = Some(partition_key);
         self
     }
-    fn sort_key(&mut self, sort_key: ()) -> &mut Self {
+    const fn sort_key(&mut self, sort_key: ()) -> &mut Self {
         self.sort_key = Some(sort_key);
         self
     }
@@ -43,13 +68,100 @@ impl Builder {
         self.with = Some(with);
         self
     }
+    const fn skip(&mut self, skip: ()) -> &mut Self {
+        self.skip = Some(skip);
+        self
+    }
+    fn default_value(&mut self, default: Option<syn::Path>) -> &mut Self {
+        self.default = Some(default.map_or(DefaultValue::Trait, DefaultValue::Path));
+        self
+    }
+    const fn skip_if_none(&mut self, skip_if_none: ()) -> &mut Self {
+        self.skip_if_none = Some(skip_if_none);
+        self
+    }
+    const fn flatten(&mut self, flatten: ()) -> &mut Self {
+        self.flatten = Some(flatten);
+        self
+    }
+    const fn version(&mut self, version: ()) -> &mut Self {
+        self.version = Some(version);
+        self
+    }
+    const fn created_at(&mut self, created_at: ()) -> &mut Self {
+        self.created_at = Some(created_at);
+        self
+    }
+    const fn updated_at(&mut self, updated_at: ()) -> &mut Self {
+        self.updated_at = Some(updated_at);
+        self
+    }
+    const fn ttl(&mut self, ttl: ()) -> &mut Self {
+        self.ttl = Some(ttl);
+        self
+    }
 }
 
 impl TryFrom<Builder> for Attr {
-    type Error = Infallible;
+    type Error = &'static str;
     fn try_from(value: Builder) -> Result<Self, Self::Error> {
-        let Builder { rename, partition_key, sort_key, with } = value;
-        Ok(Self { rename, partition_key, sort_key, with })
+        let Builder {
+            rename,
+            partition_key,
+            sort_key,
+            with,
+            skip,
+            default,
+            skip_if_none,
+            flatten,
+            version,
+            created_at,
+            updated_at,
+            ttl,
+        } = value;
+        if partition_key.is_some() && sort_key.is_some() {
+            return Err("field cannot be both the partition key and the sort key");
+        }
+        let other = rename.is_some() || partition_key.is_some() || sort_key.is_some() || with.is_some() || default.is_some() || skip_if_none.is_some();
+        if skip.is_some() && (other || flatten.is_some()) {
+            return Err("skipped fields cannot have any other parameters");
+        }
+        if flatten.is_some() && other {
+            return Err("flattened fields cannot have any other parameters");
+        }
+        if default.is_some() && (partition_key.is_some() || sort_key.is_some()) {
+            return Err("key fields cannot have a default");
+        }
+        if skip_if_none.is_some() && (partition_key.is_some() || sort_key.is_some()) {
+            return Err("key fields cannot be skipped when none");
+        }
+        if [version, created_at, updated_at, ttl].iter().filter(|m| m.is_some()).count() > 1 {
+            return Err("field can only be one of version, created_at, updated_at or ttl");
+        }
+        if ttl.is_some() && (partition_key.is_some() || sort_key.is_some() || with.is_some() || skip.is_some() || flatten.is_some()) {
+            return Err("ttl fields are always stored as seconds, and cannot be converted with another type");
+        }
+        if version.is_some() && with.is_some() {
+            return Err("version fields can only be renamed");
+        }
+        let marker = version.is_some() || created_at.is_some() || updated_at.is_some();
+        if marker && (partition_key.is_some() || sort_key.is_some() || skip.is_some() || default.is_some() || skip_if_none.is_some() || flatten.is_some()) {
+            return Err("version and timestamp fields can only be renamed or converted with another type");
+        }
+        Ok(Self {
+            rename,
+            partition_key,
+            sort_key,
+            with,
+            skip,
+            default,
+            skip_if_none,
+            flatten,
+            version,
+            created_at,
+            updated_at,
+            ttl,
+        })
     }
 }
 
@@ -60,6 +172,14 @@ impl AttrBuilder for Builder {
             "partition_key" => self.partition_key(empty(tokens)?),
             "sort_key" => self.sort_key(empty(tokens)?),
             "with" => self.with(equal(tokens)?),
+            "skip" => self.skip(empty(tokens)?),
+            "default" => self.default_value(optional_equal(tokens)?),
+            "skip_if_none" => self.skip_if_none(empty(tokens)?),
+            "flatten" => self.flatten(empty(tokens)?),
+            "version" => self.version(empty(tokens)?),
+            "created_at" => self.created_at(empty(tokens)?),
+            "updated_at" => self.updated_at(empty(tokens)?),
+            "ttl" => self.ttl(empty(tokens)?),
             _ => return Err(syn::Error::new_spanned(ident, "unknown parameter")),
         };
         Ok(())
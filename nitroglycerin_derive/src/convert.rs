@@ -1,16 +1,84 @@
 use std::convert::TryFrom;
 
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::{quote, quote_spanned, ToTokens};
-use syn::{parse_quote, Generics, Ident};
+use syn::{parse_quote, spanned::Spanned, Generics, Ident};
 
-use crate::{Column, NamedField};
+use crate::{
+    attr::{container, field::DefaultValue},
+    from_av_fn, into_av_fn,
+    template::Template,
+    templates, Column, EntityVariant, Marker, NamedField, UnnamedField,
+};
 
-impl<'a> crate::Builder for Builder<'a> {
-    fn parse(_vis: syn::Visibility, name: syn::Ident, generics: syn::Generics, _attrs: Vec<syn::Attribute>, fields: syn::FieldsNamed) -> syn::Result<TokenStream> {
+/// Versions are unsigned integers that convert losslessly into a `u64`
+fn is_version(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(p) if p.qself.is_none() => p.path.get_ident().is_some_and(|i| ["u8", "u16", "u32", "u64"].iter().any(|n| i == n)),
+        _ => false,
+    }
+}
+
+impl crate::Builder for Builder<'_> {
+    fn parse(_vis: syn::Visibility, name: syn::Ident, generics: syn::Generics, attrs: Vec<syn::Attribute>, fields: syn::FieldsNamed) -> syn::Result<TokenStream> {
+        let attrs = container::Attr::parse_attrs(attrs)?;
+        let span = fields.span();
         let fields: Vec<_> = fields.named.into_iter().map(NamedField::try_from).collect::<syn::Result<_>>()?;
-        let columns: Vec<_> = fields.into_iter().map(Column::from).collect();
-        Ok(Builder::new(&name, &generics, &columns).to_token_stream())
+
+        if attrs.transparent.is_some() {
+            return match <[_; 1]>::try_from(fields) {
+                Ok([field]) => {
+                    let member = syn::Member::Named(field.name);
+                    Ok(TransparentBuilder::new(&name, &generics, member, field.ty, field.attrs.with).to_token_stream())
+                }
+                Err(_) => Err(syn::Error::new(span, "transparent structs must have exactly one field")),
+            };
+        }
+
+        let columns: Vec<_> = fields.into_iter().map(|f| Column::new(f, &attrs)).collect();
+        for (marker, name) in [(Marker::Version, "version"), (Marker::CreatedAt, "created_at"), (Marker::UpdatedAt, "updated_at"), (Marker::Ttl, "ttl")] {
+            if let Some(c) = columns.iter().filter(|c| c.marker == Some(marker)).nth(1) {
+                return Err(syn::Error::new_spanned(&c.ident, format!("only one field can be the {name}")));
+            }
+        }
+        if let Some(c) = columns.iter().find(|c| c.marker == Some(Marker::Version) && !is_version(&c.ty)) {
+            return Err(syn::Error::new_spanned(&c.ty, "the version must be a `u8`, `u16`, `u32` or `u64`"));
+        }
+        let (partition_key, sort_key) = templates(&attrs, &columns)?;
+        let templates: Vec<_> = partition_key.into_iter().chain(sort_key).collect();
+        Ok(Builder::new(&name, &generics, &columns, &templates).to_token_stream())
+    }
+
+    fn parse_unnamed(_vis: syn::Visibility, name: syn::Ident, generics: syn::Generics, attrs: Vec<syn::Attribute>, fields: syn::FieldsUnnamed) -> syn::Result<TokenStream> {
+        let attrs = container::Attr::parse_attrs(attrs)?;
+        let span = fields.span();
+        let fields: Vec<_> = fields.unnamed.into_iter().enumerate().map(UnnamedField::try_from).collect::<syn::Result<_>>()?;
+
+        if attrs.transparent.is_some() {
+            return match <[_; 1]>::try_from(fields) {
+                Ok([field]) => {
+                    let member = syn::Member::Unnamed(field.index);
+                    Ok(TransparentBuilder::new(&name, &generics, member, field.ty, field.attrs.with).to_token_stream())
+                }
+                Err(_) => Err(syn::Error::new(span, "transparent structs must have exactly one field")),
+            };
+        }
+
+        Ok(TupleBuilder::new(&name, &generics, &fields).to_token_stream())
+    }
+
+    fn parse_enum(_vis: syn::Visibility, name: syn::Ident, generics: syn::Generics, attrs: Vec<syn::Attribute>, data: syn::DataEnum) -> syn::Result<TokenStream> {
+        let attrs = container::Attr::parse_attrs(attrs)?;
+        let tag = attrs.tag.ok_or_else(|| syn::Error::new(name.span(), "enums need a `#[nitro(tag = \"...\")]` attribute to store the entity type in"))?;
+        let variants: Vec<_> = data.variants.into_iter().map(EntityVariant::try_from).collect::<syn::Result<_>>()?;
+
+        for (i, v) in variants.iter().enumerate() {
+            if variants[..i].iter().any(|w| w.tag == v.tag) {
+                return Err(syn::Error::new(v.ident.span(), format!("duplicate entity type {:?}", v.tag)));
+            }
+        }
+
+        Ok(TaggedBuilder::new(&name, &generics, tag.value(), &variants).to_token_stream())
     }
 }
 
@@ -20,15 +88,15 @@ pub struct Builder<'a> {
 }
 
 impl<'a> Builder<'a> {
-    fn new(ident: &'a Ident, generics: &'a Generics, columns: &'a [Column]) -> Self {
+    fn new(ident: &'a Ident, generics: &'a Generics, columns: &'a [Column], templates: &'a [Template]) -> Self {
         Self {
-            from: FromBuilder::new(ident, generics, columns),
-            into: IntoBuilder::new(ident, generics, columns),
+            from: FromBuilder::new(ident, generics, columns, templates),
+            into: IntoBuilder::new(ident, generics, columns, templates),
         }
     }
 }
 
-impl<'a> ToTokens for Builder<'a> {
+impl ToTokens for Builder<'_> {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let Self { from, into } = self;
         from.to_tokens(tokens);
@@ -40,44 +108,84 @@ struct IntoBuilder<'a> {
     pub ident: &'a Ident,
     pub generics: Generics,
     pub columns: &'a [Column],
+    pub templates: &'a [Template],
 }
 
 impl<'a> IntoBuilder<'a> {
-    fn new(ident: &'a Ident, generics: &'a Generics, columns: &'a [Column]) -> Self {
+    fn new(ident: &'a Ident, generics: &'a Generics, columns: &'a [Column], templates: &'a [Template]) -> Self {
         let mut generics = generics.clone();
         let where_clause = generics.make_where_clause();
 
-        for column in columns.iter().filter(|c| c.with.is_none()) {
+        for template in templates {
+            for column in &template.components {
+                let ty = &column.ty;
+                where_clause.predicates.push(parse_quote! {
+                    #ty: ::std::fmt::Display
+                });
+            }
+        }
+
+        for column in columns.iter().filter(|c| matches!(c.marker, Some(Marker::CreatedAt | Marker::UpdatedAt))) {
             let ty = &column.ty;
             where_clause.predicates.push(parse_quote! {
-                #ty: ::nitroglycerin::convert::IntoAttributeValue
+                #ty: ::nitroglycerin::timestamp::Timestamp
             });
         }
 
-        Self { ident, generics, columns }
+        for column in columns.iter().filter(|c| !c.skip && c.with.is_none() && !templates.iter().any(|t| t.contains(&c.ident))) {
+            let ty = &column.ty;
+            if column.flatten {
+                where_clause.predicates.push(parse_quote! {
+                    #ty: ::std::convert::Into<::nitroglycerin::Attributes> + ::nitroglycerin::timestamp::Timestamped
+                });
+            } else {
+                where_clause.predicates.push(parse_quote! {
+                    #ty: ::nitroglycerin::convert::IntoAttributeValue
+                });
+            }
+        }
+
+        Self { ident, generics, columns, templates }
     }
 }
 
-impl<'a> ToTokens for IntoBuilder<'a> {
+impl ToTokens for IntoBuilder<'_> {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        let Self { ident, generics, columns } = self;
+        let Self { ident, generics, columns, templates } = self;
 
         let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-        let intos = columns.iter().map(|c| {
-            let Column { ident, name, ty, with } = c;
-            match with {
-                None => quote_spanned! { ident.span() => (#name.to_owned(), <#ty as ::nitroglycerin::convert::IntoAttributeValue>::into_av(t.#ident)) },
-                Some(with) => quote_spanned! { ident.span() => (#name.to_owned(), #with::into_av(t.#ident)) },
+        let keys = templates.iter().map(|t| {
+            let name = &t.name;
+            let components = t.components.iter().map(|c| &c.ident);
+            let value = t.compose(&quote! { [ #( ::std::string::ToString::to_string(&t.#components) ),* ] });
+            quote! { a.insert(#name.to_owned(), ::nitroglycerin::convert::IntoAttributeValue::into_av(#value)); }
+        });
+
+        let intos = columns.iter().filter(|c| !c.skip && !templates.iter().any(|t| t.contains(&c.ident))).map(|c| {
+            let Column { ident, name, ty, with, skip_if_none, flatten, .. } = c;
+            let into_av = into_av_fn(ty, with.as_ref());
+            if *flatten {
+                quote_spanned! { ident.span() => a.extend(<#ty as ::std::convert::Into<::nitroglycerin::Attributes>>::into(t.#ident)); }
+            } else if *skip_if_none {
+                quote_spanned! { ident.span() =>
+                    let av = #into_av(t.#ident);
+                    if !::nitroglycerin::convert::is_null(&av) {
+                        a.insert(#name.to_owned(), av);
+                    }
+                }
+            } else {
+                quote_spanned! { ident.span() => a.insert(#name.to_owned(), #into_av(t.#ident)); }
             }
         });
 
         tokens.extend(quote! {
             impl #impl_generics ::std::convert::From<#ident #ty_generics> for ::nitroglycerin::Attributes #where_clause {
                 fn from(t: #ident #ty_generics) -> Self {
-                    <_>::into_iter([
-                        #( #intos ),*
-                    ]).collect()
+                    let mut a = Self::new();
+                    #( #keys )*
+                    #( #intos )*
+                    a
                 }
             }
 
@@ -91,6 +199,122 @@ impl<'a> ToTokens for IntoBuilder<'a> {
                 }
             }
         });
+
+        self.marker_impls(tokens);
+    }
+}
+
+impl IntoBuilder<'_> {
+    /// implementations of the traits that put and update requests use to handle `Marker` fields
+    fn marker_impls(&self, tokens: &mut proc_macro2::TokenStream) {
+        let Self { ident, generics, columns, .. } = self;
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let version_column = columns.iter().find(|c| c.marker == Some(Marker::Version));
+        let has_version = version_column.map(|Column { name, .. }| {
+            quote! {
+                impl #impl_generics ::nitroglycerin::version::HasVersion for #ident #ty_generics #where_clause {
+                    fn version_attribute_name() -> &'static str {
+                        #name
+                    }
+                }
+            }
+        });
+        let version = version_column.map(|c| {
+            let Column { ident, name, ty, .. } = c;
+            quote_spanned! { ident.span() =>
+                fn version_attribute() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some(#name)
+                }
+
+                fn version(&self) -> u64 {
+                    <u64 as ::std::convert::From<#ty>>::from(self.#ident)
+                }
+
+                fn next_version(&self) -> ::std::option::Option<u64> {
+                    self.#ident.checked_add(1).map(<u64 as ::std::convert::From<#ty>>::from)
+                }
+            }
+        });
+
+        let created_at = columns.iter().find(|c| c.marker == Some(Marker::CreatedAt));
+        let updated_at = columns.iter().find(|c| c.marker == Some(Marker::UpdatedAt));
+
+        let flattened: Vec<_> = columns.iter().filter(|c| c.flatten && !c.skip).collect();
+
+        let touch = (created_at.is_some() || updated_at.is_some() || !flattened.is_empty()).then(|| {
+            let created_at = created_at.map(|Column { ident, ty, .. }| {
+                quote_spanned! { ident.span() =>
+                    if ::nitroglycerin::timestamp::Timestamp::is_unset(&self.#ident) {
+                        self.#ident = <#ty as ::nitroglycerin::timestamp::Timestamp>::from_system_time(now);
+                    }
+                }
+            });
+            let updated_at = updated_at.map(|Column { ident, ty, .. }| {
+                quote_spanned! { ident.span() => self.#ident = <#ty as ::nitroglycerin::timestamp::Timestamp>::from_system_time(now); }
+            });
+            let flattened = flattened.iter().map(|Column { ident, ty, .. }| {
+                quote_spanned! { ident.span() => <#ty as ::nitroglycerin::timestamp::Timestamped>::touch(&mut self.#ident, now); }
+            });
+            quote! {
+                fn touch(&mut self, now: ::std::time::SystemTime) {
+                    #created_at
+                    #updated_at
+                    #( #flattened )*
+                }
+            }
+        });
+
+        // an item's own timestamp column wins over the ones of its flattened fields
+        let timestamp_fn = |f: &str, column: Option<&Column>| {
+            let f = syn::Ident::new(f, Span::call_site());
+            if let Some(Column { ident, name, ty, with, .. }) = column {
+                let into_av = into_av_fn(ty, with.as_ref());
+                Some(quote_spanned! { ident.span() =>
+                    fn #f(now: ::std::time::SystemTime) -> ::std::option::Option<(&'static str, ::nitroglycerin::dynamodb::AttributeValue)> {
+                        ::std::option::Option::Some((#name, #into_av(<#ty as ::nitroglycerin::timestamp::Timestamp>::from_system_time(now))))
+                    }
+                })
+            } else if flattened.is_empty() {
+                None
+            } else {
+                let tys = flattened.iter().map(|c| &c.ty);
+                Some(quote! {
+                    fn #f(now: ::std::time::SystemTime) -> ::std::option::Option<(&'static str, ::nitroglycerin::dynamodb::AttributeValue)> {
+                        ::std::option::Option::None #( .or_else(|| <#tys as ::nitroglycerin::timestamp::Timestamped>::#f(now)) )*
+                    }
+                })
+            }
+        };
+        let ttl = columns.iter().find(|c| c.marker == Some(Marker::Ttl)).map(|Column { name, .. }| {
+            quote! {
+                impl #impl_generics ::nitroglycerin::ttl::TimeToLive for #ident #ty_generics #where_clause {
+                    fn ttl_attribute() -> &'static str {
+                        #name
+                    }
+                }
+            }
+        });
+
+        let created_at = timestamp_fn("created_at", created_at);
+        let updated_at = timestamp_fn("updated_at", updated_at);
+
+        tokens.extend(quote! {
+            impl #impl_generics ::nitroglycerin::version::Versioned for #ident #ty_generics #where_clause {
+                #version
+            }
+
+            #has_version
+
+            impl #impl_generics ::nitroglycerin::timestamp::Timestamped for #ident #ty_generics #where_clause {
+                #touch
+                #created_at
+                #updated_at
+            }
+
+            #ttl
+        });
     }
 }
 
@@ -98,51 +322,381 @@ struct FromBuilder<'a> {
     pub ident: &'a Ident,
     pub generics: Generics,
     pub columns: &'a [Column],
+    pub templates: &'a [Template],
 }
 
 impl<'a> FromBuilder<'a> {
-    fn new(ident: &'a Ident, generics: &'a Generics, columns: &'a [Column]) -> Self {
+    fn new(ident: &'a Ident, generics: &'a Generics, columns: &'a [Column], templates: &'a [Template]) -> Self {
         let mut generics = generics.clone();
         let where_clause = generics.make_where_clause();
 
-        for column in columns.iter().filter(|c| c.with.is_none()) {
+        for column in columns {
             let ty = &column.ty;
+            if templates.iter().any(|t| t.contains(&column.ident)) {
+                where_clause.predicates.push(parse_quote! {
+                    #ty: ::std::str::FromStr
+                });
+                where_clause.predicates.push(parse_quote! {
+                    <#ty as ::std::str::FromStr>::Err: ::std::error::Error + 'static
+                });
+            } else if column.flatten {
+                where_clause.predicates.push(parse_quote! {
+                    #ty: ::std::convert::TryFrom<::nitroglycerin::Attributes, Error = ::nitroglycerin::AttributeError>
+                });
+            } else if !column.skip && column.with.is_none() {
+                where_clause.predicates.push(parse_quote! {
+                    #ty: ::nitroglycerin::convert::FromAttributeValue
+                });
+            }
+            if column.skip || matches!(column.default, Some(DefaultValue::Trait)) {
+                where_clause.predicates.push(parse_quote! {
+                    #ty: ::std::default::Default
+                });
+            }
+        }
+
+        Self { ident, generics, columns, templates }
+    }
+}
+
+impl ToTokens for FromBuilder<'_> {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let Self { ident, generics, columns, templates } = self;
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        // flattened fields take whatever attributes are left over, so they must be extracted last
+        let (flattened, columns): (Vec<_>, Vec<_>) = columns.iter().partition(|c| c.flatten);
+
+        // every field is parsed before failing, so that all of their errors are reported together
+        let errors = Ident::new("errors", Span::mixed_site());
+
+        let keys = templates.iter().enumerate().map(|(i, t)| t.decompose(&errors, i));
+
+        // template components are decomposed from their key attribute above
+        let (skipped, parsed): (Vec<_>, Vec<_>) = columns.iter().copied().filter(|c| !templates.iter().any(|t| t.contains(&c.ident))).partition(|c| c.skip);
+
+        let skips = skipped.iter().map(|Column { ident, .. }| quote_spanned! { ident.span() => let #ident = ::std::default::Default::default(); });
+
+        let extracts = parsed.iter().map(|c| {
+            let Column { ident, name, ty, with, default, skip_if_none, .. } = c;
+            let from_av = from_av_fn(ty, with.as_ref());
+            let in_field = quote! { |e| e.in_field(#name) };
+            let value = match default {
+                None if *skip_if_none => quote_spanned! { ident.span() =>
+                    match a.remove(#name) {
+                        ::std::option::Option::Some(av) => #from_av(av).map_err(#in_field),
+                        ::std::option::Option::None => #from_av(::nitroglycerin::convert::null()).map_err(|_| ::nitroglycerin::AttributeError::MissingField(#name.to_owned())),
+                    }
+                },
+                None => quote_spanned! { ident.span() =>
+                    a.remove(#name).ok_or_else(|| ::nitroglycerin::AttributeError::MissingField(#name.to_owned())).and_then(|av| #from_av(av).map_err(#in_field))
+                },
+                Some(DefaultValue::Trait) => quote_spanned! { ident.span() =>
+                    a.remove(#name).map(#from_av).transpose().map_err(#in_field).map(::std::option::Option::unwrap_or_default)
+                },
+                Some(DefaultValue::Path(path)) => quote_spanned! { ident.span() =>
+                    a.remove(#name).map(#from_av).transpose().map_err(#in_field).map(|v| v.unwrap_or_else(#path))
+                },
+            };
+            quote_spanned! { ident.span() => let #ident = #errors.check(#value); }
+        });
+
+        let flattens = flattened.iter().enumerate().map(|(i, c)| {
+            let Column { ident, ty, .. } = c;
+            let attributes = if i + 1 == flattened.len() {
+                quote! { ::std::mem::take(&mut a) }
+            } else {
+                quote! { a.clone() }
+            };
+            quote_spanned! { ident.span() => let #ident = #errors.check(<#ty as ::std::convert::TryFrom<::nitroglycerin::Attributes>>::try_from(#attributes)); }
+        });
+
+        let components = templates.iter().flat_map(|t| &t.components);
+        let checked: Vec<_> = components.chain(parsed.iter().copied()).chain(flattened.iter().copied()).map(|c| &c.ident).collect();
+        let idents = columns.iter().chain(&flattened).map(|c| &c.ident);
+
+        let build = if checked.is_empty() {
+            quote! { ::std::result::Result::Ok(Self { #( #idents ),* }) }
+        } else {
+            quote! {
+                let mut #errors = ::nitroglycerin::convert::Errors::new();
+                #( #keys )*
+                #( #extracts )*
+                #( #flattens )*
+                match ( #( #checked, )* ) {
+                    ( #( ::std::option::Option::Some(#checked), )* ) => ::std::result::Result::Ok(Self { #( #idents ),* }),
+                    _ => ::std::result::Result::Err(#errors.into_error()),
+                }
+            }
+        };
+
+        tokens.extend(quote! {
+            impl #impl_generics ::std::convert::TryFrom<::nitroglycerin::Attributes> for #ident #ty_generics #where_clause {
+                type Error = ::nitroglycerin::AttributeError;
+                fn try_from(mut a: ::nitroglycerin::Attributes) -> ::std::result::Result<Self, Self::Error> {
+                    #( #skips )*
+                    #build
+                }
+            }
+        });
+
+        tokens.extend(quote! {
+            impl #impl_generics ::nitroglycerin::convert::FromAttributeValue for #ident #ty_generics #where_clause {
+                fn try_from_av(av: ::nitroglycerin::dynamodb::AttributeValue) -> ::std::result::Result<Self, ::nitroglycerin::AttributeError> {
+                    ::nitroglycerin::convert::expect(av, ::nitroglycerin::convert::AttributeType::M, |av| av.m.take()).and_then(
+                        <Self as ::std::convert::TryFrom<::nitroglycerin::Attributes>>::try_from
+                    )
+                }
+            }
+        });
+    }
+}
+
+/// Converts a single field struct exactly like the type it wraps
+struct TransparentBuilder<'a> {
+    pub ident: &'a Ident,
+    pub generics: Generics,
+    pub member: syn::Member,
+    pub ty: syn::Type,
+    pub with: Option<syn::Path>,
+}
+
+impl<'a> TransparentBuilder<'a> {
+    fn new(ident: &'a Ident, generics: &Generics, member: syn::Member, ty: syn::Type, with: Option<syn::Path>) -> Self {
+        let mut generics = generics.clone();
+        if with.is_none() {
+            let where_clause = generics.make_where_clause();
+            where_clause.predicates.push(parse_quote! {
+                #ty: ::nitroglycerin::convert::FromAttributeValue + ::nitroglycerin::convert::IntoAttributeValue
+            });
+        }
+
+        Self { ident, generics, member, ty, with }
+    }
+}
+
+impl ToTokens for TransparentBuilder<'_> {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let Self { ident, generics, member, ty, with } = self;
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let into_av = into_av_fn(ty, with.as_ref());
+        let from_av = from_av_fn(ty, with.as_ref());
+
+        tokens.extend(quote! {
+            impl #impl_generics ::nitroglycerin::convert::IntoAttributeValue for #ident #ty_generics #where_clause {
+                fn into_av(self) -> ::nitroglycerin::dynamodb::AttributeValue {
+                    #into_av(self.#member)
+                }
+            }
+
+            impl #impl_generics ::nitroglycerin::convert::FromAttributeValue for #ident #ty_generics #where_clause {
+                fn try_from_av(av: ::nitroglycerin::dynamodb::AttributeValue) -> ::std::result::Result<Self, ::nitroglycerin::AttributeError> {
+                    ::std::result::Result::Ok(Self { #member: #from_av(av)? })
+                }
+            }
+        });
+    }
+}
+
+/// Converts a tuple struct to and from a list attribute value
+struct TupleBuilder<'a> {
+    pub ident: &'a Ident,
+    pub generics: Generics,
+    pub fields: &'a [UnnamedField],
+}
+
+impl<'a> TupleBuilder<'a> {
+    fn new(ident: &'a Ident, generics: &Generics, fields: &'a [UnnamedField]) -> Self {
+        let mut generics = generics.clone();
+        let where_clause = generics.make_where_clause();
+
+        for field in fields.iter().filter(|f| f.attrs.with.is_none()) {
+            let ty = &field.ty;
+            where_clause.predicates.push(parse_quote! {
+                #ty: ::nitroglycerin::convert::FromAttributeValue + ::nitroglycerin::convert::IntoAttributeValue
+            });
+        }
+
+        Self { ident, generics, fields }
+    }
+}
+
+impl ToTokens for TupleBuilder<'_> {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let Self { ident, generics, fields } = self;
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let intos = fields.iter().map(|f| {
+            let UnnamedField { attrs, index, ty } = f;
+            let into_av = into_av_fn(ty, attrs.with.as_ref());
+            quote_spanned! { ty.span() => #into_av(self.#index) }
+        });
+
+        let len = fields.len();
+        let froms = fields.iter().enumerate().map(|(i, f)| {
+            let UnnamedField { attrs, index, ty } = f;
+            let from_av = from_av_fn(ty, attrs.with.as_ref());
+            quote_spanned! { ty.span() => #index: #from_av(l.next().ok_or_else(incorrect_length)?).map_err(|e| e.in_index(#i))? }
+        });
+
+        tokens.extend(quote! {
+            impl #impl_generics ::nitroglycerin::convert::IntoAttributeValue for #ident #ty_generics #where_clause {
+                fn into_av(self) -> ::nitroglycerin::dynamodb::AttributeValue {
+                    ::nitroglycerin::dynamodb::AttributeValue {
+                        l: ::std::option::Option::Some(::std::vec![ #( #intos ),* ]),
+                        ..::nitroglycerin::dynamodb::AttributeValue::default()
+                    }
+                }
+            }
+
+            impl #impl_generics ::nitroglycerin::convert::FromAttributeValue for #ident #ty_generics #where_clause {
+                fn try_from_av(av: ::nitroglycerin::dynamodb::AttributeValue) -> ::std::result::Result<Self, ::nitroglycerin::AttributeError> {
+                    let l = ::nitroglycerin::convert::expect(av, ::nitroglycerin::convert::AttributeType::L, |av| av.l.take())?;
+                    let found = l.len();
+                    let incorrect_length = || ::nitroglycerin::AttributeError::IncorrectLength { expected: #len, found };
+                    if found > #len {
+                        return ::std::result::Result::Err(incorrect_length());
+                    }
+                    let mut l = l.into_iter();
+                    ::std::result::Result::Ok(Self { #( #froms ),* })
+                }
+            }
+        });
+    }
+}
+
+/// Converts an enum of entity types, using a tag attribute to tell them apart
+struct TaggedBuilder<'a> {
+    pub ident: &'a Ident,
+    pub generics: Generics,
+    pub tag: String,
+    pub variants: &'a [EntityVariant],
+}
+
+impl<'a> TaggedBuilder<'a> {
+    fn new(ident: &'a Ident, generics: &Generics, tag: String, variants: &'a [EntityVariant]) -> Self {
+        let mut generics = generics.clone();
+        let where_clause = generics.make_where_clause();
+
+        for variant in variants {
+            let ty = &variant.ty;
             where_clause.predicates.push(parse_quote! {
-                #ty: ::nitroglycerin::convert::FromAttributeValue
+                #ty: ::std::convert::Into<::nitroglycerin::Attributes> + ::std::convert::TryFrom<::nitroglycerin::Attributes, Error = ::nitroglycerin::AttributeError>
+                    + ::nitroglycerin::version::Versioned + ::nitroglycerin::timestamp::Timestamped
             });
         }
 
-        Self { ident, generics, columns }
+        Self { ident, generics, tag, variants }
     }
 }
 
-impl<'a> ToTokens for FromBuilder<'a> {
+impl ToTokens for TaggedBuilder<'_> {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        let Self { ident, generics, columns } = self;
+        let Self { ident, generics, tag, variants } = self;
 
         let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-        let extracts = columns.iter().map(|c| {
-            let Column { ident, name, ty, with } = c;
-            match with {
-                None => quote_spanned! { ident.span() => #ident: ::nitroglycerin::convert::extract::<#ty>(&mut a, #name)? },
-                Some(with) => quote_spanned! { ident.span() => #ident: #with::try_from_av(a.remove(#name).ok_or_else(|| ::nitroglycerin::AttributeError::MissingField(#name.to_owned()))?)? },
+        let intos = variants.iter().map(|v| {
+            let EntityVariant { ident: variant, tag: value, ty } = v;
+            quote_spanned! { variant.span() =>
+                #ident::#variant(t) => (#value, <#ty as ::std::convert::Into<::nitroglycerin::Attributes>>::into(t)),
+            }
+        });
+
+        // every variant is passed to the same trait method
+        let delegate = |method: TokenStream| {
+            let arms = variants.iter().map(|v| {
+                let EntityVariant { ident: variant, .. } = v;
+                quote_spanned! { variant.span() => #ident::#variant(t) => #method, }
+            });
+            quote! {
+                match self {
+                    #( #arms )*
+                }
+            }
+        };
+        let touch = delegate(quote! { ::nitroglycerin::timestamp::Timestamped::touch(t, now) });
+        let created_at = delegate(quote! { ::nitroglycerin::timestamp::Timestamped::item_created_at(t, now) });
+        let updated_at = delegate(quote! { ::nitroglycerin::timestamp::Timestamped::item_updated_at(t, now) });
+        let version_attribute = delegate(quote! { ::nitroglycerin::version::Versioned::item_version_attribute(t) });
+        let version = delegate(quote! { ::nitroglycerin::version::Versioned::version(t) });
+        let next_version = delegate(quote! { ::nitroglycerin::version::Versioned::next_version(t) });
+
+        let froms = variants.iter().map(|v| {
+            let EntityVariant { ident: variant, tag: value, ty } = v;
+            quote_spanned! { variant.span() =>
+                #value => <#ty as ::std::convert::TryFrom<::nitroglycerin::Attributes>>::try_from(a).map(Self::#variant),
             }
         });
 
         tokens.extend(quote! {
+            impl #impl_generics ::std::convert::From<#ident #ty_generics> for ::nitroglycerin::Attributes #where_clause {
+                fn from(t: #ident #ty_generics) -> Self {
+                    let (tag, mut a) = match t {
+                        #( #intos )*
+                    };
+                    a.insert(#tag.to_owned(), ::nitroglycerin::convert::IntoAttributeValue::into_av(tag.to_owned()));
+                    a
+                }
+            }
+
+            impl #impl_generics ::nitroglycerin::convert::IntoAttributeValue for #ident #ty_generics #where_clause {
+                fn into_av(self) -> ::nitroglycerin::dynamodb::AttributeValue {
+                    ::nitroglycerin::dynamodb::AttributeValue {
+                        m: ::std::option::Option::Some(<Self as ::std::convert::Into<::nitroglycerin::Attributes>>::into(self)),
+                        ..::nitroglycerin::dynamodb::AttributeValue::default()
+                    }
+                }
+            }
+
+            impl #impl_generics ::nitroglycerin::version::Versioned for #ident #ty_generics #where_clause {
+                fn item_version_attribute(&self) -> ::std::option::Option<&'static str> {
+                    #version_attribute
+                }
+
+                fn version(&self) -> u64 {
+                    #version
+                }
+
+                fn next_version(&self) -> ::std::option::Option<u64> {
+                    #next_version
+                }
+            }
+
+            impl #impl_generics ::nitroglycerin::timestamp::Timestamped for #ident #ty_generics #where_clause {
+                fn touch(&mut self, now: ::std::time::SystemTime) {
+                    #touch
+                }
+
+                fn item_created_at(&self, now: ::std::time::SystemTime) -> ::std::option::Option<(&'static str, ::nitroglycerin::dynamodb::AttributeValue)> {
+                    #created_at
+                }
+
+                fn item_updated_at(&self, now: ::std::time::SystemTime) -> ::std::option::Option<(&'static str, ::nitroglycerin::dynamodb::AttributeValue)> {
+                    #updated_at
+                }
+            }
+
             impl #impl_generics ::std::convert::TryFrom<::nitroglycerin::Attributes> for #ident #ty_generics #where_clause {
                 type Error = ::nitroglycerin::AttributeError;
                 fn try_from(mut a: ::nitroglycerin::Attributes) -> ::std::result::Result<Self, Self::Error> {
-                    Ok(Self { #( #extracts ),* })
+                    let tag: ::std::string::String = ::nitroglycerin::convert::FromAttributeValue::try_from_av(
+                        a.remove(#tag).ok_or_else(|| ::nitroglycerin::AttributeError::MissingField(#tag.to_owned()))?
+                    )?;
+                    match tag.as_str() {
+                        #( #froms )*
+                        _ => ::std::result::Result::Err(::nitroglycerin::AttributeError::UnknownType(tag)),
+                    }
                 }
             }
-        });
 
-        tokens.extend(quote! {
             impl #impl_generics ::nitroglycerin::convert::FromAttributeValue for #ident #ty_generics #where_clause {
                 fn try_from_av(av: ::nitroglycerin::dynamodb::AttributeValue) -> ::std::result::Result<Self, ::nitroglycerin::AttributeError> {
-                    av.m.ok_or(::nitroglycerin::AttributeError::IncorrectType).and_then(
+                    ::nitroglycerin::convert::expect(av, ::nitroglycerin::convert::AttributeType::M, |av| av.m.take()).and_then(
                         <Self as ::std::convert::TryFrom<::nitroglycerin::Attributes>>::try_from
                     )
                 }
@@ -48,6 +48,8 @@ impl<'a, I: 'a + Iterator, F: FnMut(&I::Item) -> bool> Iterator for SplitBy<'a,
 }
 
 impl<'a, I: 'a + Iterator, F> SplitBy<'a, I, F> {
+    // `mem::replace` is only const since rust 1.83
+    #[allow(clippy::missing_const_for_fn)]
     pub fn done(&mut self) -> SplitByState<I::Item> {
         std::mem::replace(&mut self.state, SplitByState::Continue)
     }
@@ -1,23 +1,60 @@
 use std::convert::TryFrom;
 
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::TokenStream;
 use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{parse_quote, Generics, Ident, Type, Visibility};
 
-use crate::{Column, D, DL, NamedField};
+use crate::{attr::container, key_columns, template::Chain, Column, KeyColumn, NamedField, D, DL};
 
-impl<'a> crate::Builder for Builder<'a> {
-    fn parse(vis: syn::Visibility, name: syn::Ident, generics: syn::Generics, _attrs: Vec<syn::Attribute>, fields: syn::FieldsNamed) -> syn::Result<TokenStream> {
+/// Counters can only be kept in number attributes
+fn is_number(ty: &Type) -> bool {
+    const NUMBERS: &[&str] = &["i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32", "f64"];
+    match ty {
+        Type::Path(p) if p.qself.is_none() => p.path.get_ident().is_some_and(|i| NUMBERS.iter().any(|n| i == n)),
+        _ => false,
+    }
+}
+
+impl crate::Builder for Builder<'_> {
+    fn parse(vis: syn::Visibility, name: syn::Ident, generics: syn::Generics, attrs: Vec<syn::Attribute>, fields: syn::FieldsNamed) -> syn::Result<TokenStream> {
+        let attrs = container::Attr::parse_attrs(attrs)?;
         let fields: Vec<_> = fields.named.into_iter().map(NamedField::try_from).collect::<syn::Result<_>>()?;
+        let (partition_key, sort_key) = key_columns(&fields, &attrs)?;
 
-        let partition_key: Column = fields
-            .iter()
-            .find_map(|f| f.attrs.partition_key.map(|_| f.clone().into()))
-            .ok_or_else(|| syn::Error::new(Span::call_site(), "table needs a partition key"))?;
+        let is_key = |c: &Column| {
+            [&partition_key, sort_key.as_ref().unwrap_or(&partition_key)].iter().any(|k| match k {
+                KeyColumn::Field(f) => f.ident == c.ident,
+                KeyColumn::Template(t) => t.contains(&c.ident),
+            })
+        };
+        let counters = fields
+            .into_iter()
+            .map(|f| Column::new(f, &attrs))
+            .filter(|c| !is_key(c) && !c.skip && !c.flatten && c.with.is_none() && c.marker.is_none() && is_number(&c.ty))
+            .collect();
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let partition_key_name = partition_key.name();
+        let sort_key_name = sort_key.as_ref().map(KeyColumn::name).map_or_else(
+            || quote! { ::std::option::Option::None },
+            |name| quote! { ::std::option::Option::Some(#name) },
+        );
+        let primary_key = quote! {
+            impl #impl_generics ::nitroglycerin::key::PrimaryKey for #name #ty_generics #where_clause {
+                fn partition_key_name() -> &'static str {
+                    #partition_key_name
+                }
 
-        let sort_key = fields.iter().find_map(|f| f.attrs.sort_key.map(|_| f.clone().into()));
+                fn sort_key_name() -> ::std::option::Option<&'static str> {
+                    #sort_key_name
+                }
+            }
+        };
 
-        Ok(Builder::new(&vis, &name, &generics, partition_key, sort_key).into_token_stream())
+        let mut tokens = Builder::new(&vis, &name, &generics, partition_key, sort_key).into_token_stream();
+        tokens.extend(primary_key);
+        CountersBuilder::new(&vis, &name, &generics, counters).to_tokens(&mut tokens);
+        Ok(tokens)
     }
 }
 
@@ -34,6 +71,7 @@ impl quote::ToTokens for R {
     }
 }
 
+#[allow(clippy::struct_field_names)]
 pub struct Builder<'a> {
     trait_builder: TraitBuilder<'a>,
     key_builder1: Builder1<'a>,
@@ -41,7 +79,7 @@ pub struct Builder<'a> {
 }
 
 impl<'a> Builder<'a> {
-    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, partition_key: Column, sort_key: Option<Column>) -> Self {
+    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, partition_key: KeyColumn, sort_key: Option<KeyColumn>) -> Self {
         Self {
             trait_builder: TraitBuilder::new(output, generics),
             key_builder1: Builder1::new(vis, output, generics, partition_key),
@@ -50,7 +88,7 @@ impl<'a> Builder<'a> {
     }
 }
 
-impl<'a> ToTokens for Builder<'a> {
+impl ToTokens for Builder<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             trait_builder,
@@ -88,7 +126,7 @@ impl<'a> TraitBuilder<'a> {
     }
 }
 
-impl<'a> ToTokens for TraitBuilder<'a> {
+impl ToTokens for TraitBuilder<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self { output, generics, new_generics } = self;
         let builder = format_ident!("{}KeyBuilder", output);
@@ -114,11 +152,11 @@ struct Builder1<'a> {
     generics: &'a Generics,
     new_generics: Generics,
     phantom_data: Type,
-    partition_key: Column,
+    partition_key: KeyColumn,
 }
 
 impl<'a> Builder1<'a> {
-    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, partition_key: Column) -> Self {
+    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, partition_key: KeyColumn) -> Self {
         let mut new_generics = generics.clone();
 
         let tys = new_generics.type_params().map(|tp| &tp.ident);
@@ -150,7 +188,7 @@ impl<'a> Builder1<'a> {
     }
 }
 
-impl<'a> ToTokens for Builder1<'a> {
+impl ToTokens for Builder1<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             vis,
@@ -163,12 +201,10 @@ impl<'a> ToTokens for Builder1<'a> {
         let builder = format_ident!("{}KeyBuilder", output);
         let builder_p = format_ident!("{}Partition", builder);
 
-        let Column { ident, name, ty, .. } = partition_key;
-
         let (impl_generics, ty_generics, where_clause) = new_generics.split_for_impl();
         let (_, ty_generics2, _) = generics.split_for_impl();
 
-        let type_doc = format!("part one of the key builder chain for {}", output);
+        let type_doc = format!("part one of the key builder chain for {output}");
 
         tokens.extend(quote! {
             #[doc = #type_doc]
@@ -178,7 +214,32 @@ impl<'a> ToTokens for Builder1<'a> {
             }
         });
 
-        let fn_doc = format!("set the value of the partition key ({})", ident);
+        let Column { ident, name, ty, .. } = match partition_key {
+            KeyColumn::Field(column) => column,
+            KeyColumn::Template(template) => {
+                let name = &template.name;
+                let value = template.compose(&quote! { parts });
+                let chain = Chain {
+                    vis,
+                    start: &builder,
+                    generics: new_generics,
+                    phantom_data,
+                    state: vec![],
+                    components: &template.components,
+                    output: quote! { #builder_p #ty_generics },
+                    bounds: quote! { #output #ty_generics2: ::nitroglycerin::Table, },
+                    finish: quote! {
+                        let key = ::nitroglycerin::key::Key::new::<#output #ty_generics2, _>(#name, #value);
+                        #builder_p::new(client, key)
+                    },
+                    partial: quote! {},
+                    doc: "partition key",
+                };
+                return chain.to_tokens(tokens);
+            }
+        };
+
+        let fn_doc = format!("set the value of the partition key ({ident})");
 
         tokens.extend(quote_spanned! { ident.span() =>
             impl #impl_generics #builder #ty_generics #where_clause {
@@ -206,11 +267,11 @@ struct Builder2<'a> {
     generics: &'a Generics,
     new_generics: Generics,
     phantom_data: Type,
-    sort_key: Option<Column>,
+    sort_key: Option<KeyColumn>,
 }
 
 impl<'a> Builder2<'a> {
-    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, sort_key: Option<Column>) -> Self {
+    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, sort_key: Option<KeyColumn>) -> Self {
         let mut new_generics = generics.clone();
 
         let tys = new_generics.type_params().map(|tp| &tp.ident);
@@ -242,7 +303,7 @@ impl<'a> Builder2<'a> {
     }
 }
 
-impl<'a> ToTokens for Builder2<'a> {
+impl ToTokens for Builder2<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             vis,
@@ -259,10 +320,10 @@ impl<'a> ToTokens for Builder2<'a> {
         let (impl_generics, ty_generics, where_clause) = new_generics.split_for_impl();
         let (_, ty_generics2, _) = generics.split_for_impl();
 
-        let type_doc = format!("part two of the key builder chain for {}", output);
+        let type_doc = format!("part two of the key builder chain for {output}");
 
         match sort_key {
-            Some(Column { ident, name, ty, .. }) => {
+            Some(KeyColumn::Template(template)) => {
                 tokens.extend(quote! {
                     #[doc = #type_doc]
                     #vis struct #builder_p #impl_generics {
@@ -278,7 +339,44 @@ impl<'a> ToTokens for Builder2<'a> {
                     }
                 });
 
-                let fn_doc = format!("set the value of the sort key ({})", ident);
+                let name = &template.name;
+                let value = template.compose(&quote! { parts });
+                Chain {
+                    vis,
+                    start: &builder_p,
+                    generics: new_generics,
+                    phantom_data,
+                    state: vec![(parse_quote! { key }, parse_quote! { ::nitroglycerin::key::Key })],
+                    components: &template.components,
+                    output: quote! { ::nitroglycerin::key::Expr<#DL, #D, #R, #output #ty_generics2> },
+                    bounds: quote! {},
+                    finish: quote! {
+                        let mut key = key;
+                        key.insert(#name, #value);
+                        ::nitroglycerin::key::Expr::new(client, key)
+                    },
+                    partial: quote! {},
+                    doc: "sort key",
+                }
+                .to_tokens(tokens);
+            }
+            Some(KeyColumn::Field(Column { ident, name, ty, .. })) => {
+                tokens.extend(quote! {
+                    #[doc = #type_doc]
+                    #vis struct #builder_p #impl_generics {
+                        client: &#DL #D,
+                        key: ::nitroglycerin::key::Key,
+                        _phantom: ::std::marker::PhantomData<#phantom_data>,
+                    }
+
+                    impl #impl_generics #builder_p #ty_generics #where_clause {
+                        fn new(client: &#DL #D, key: ::nitroglycerin::key::Key) -> Self {
+                            Self { client, key, _phantom: ::std::marker::PhantomData }
+                        }
+                    }
+                });
+
+                let fn_doc = format!("set the value of the sort key ({ident})");
 
                 tokens.extend(quote_spanned! { ident.span() =>
                     impl #impl_generics #builder_p #ty_generics #where_clause {
@@ -304,3 +402,70 @@ impl<'a> ToTokens for Builder2<'a> {
         }
     }
 }
+
+/// Extension trait adding a method per number field to increment requests
+struct CountersBuilder<'a> {
+    vis: &'a Visibility,
+    output: &'a Ident,
+    generics: &'a Generics,
+    counters: Vec<Column>,
+}
+
+impl<'a> CountersBuilder<'a> {
+    const fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, counters: Vec<Column>) -> Self {
+        Self { vis, output, generics, counters }
+    }
+}
+
+impl ToTokens for CountersBuilder<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self { vis, output, generics, counters } = self;
+        if counters.is_empty() {
+            return;
+        }
+
+        let counters_trait = format_ident!("{}Counters", output);
+
+        let (_, ty_generics, _) = generics.split_for_impl();
+        let mut new_generics = (*generics).clone();
+        new_generics.params.push(parse_quote! { #DL });
+        new_generics.params.push(parse_quote! { #D: #DL + ?Sized });
+        // the higher ranked bound defers the check until the trait is used, so tables without `#[derive(Attributes)]` still compile
+        new_generics.make_where_clause().predicates.push(parse_quote! {
+            for<'__nitroglycerin> #output #ty_generics: ::nitroglycerin::version::Versioned + ::nitroglycerin::timestamp::Timestamped
+        });
+        let (impl_generics, _, where_clause) = new_generics.split_for_impl();
+
+        let trait_doc = format!("atomic counters of {output}, used by `client.increment::<{output}>()`");
+
+        let signatures: Vec<_> = counters
+            .iter()
+            .map(|Column { ident, ty, .. }| {
+                let fn_doc = format!("atomically add to {ident}, returning its new value");
+                quote_spanned! { ident.span() =>
+                    #[doc = #fn_doc]
+                    fn #ident(self, by: #ty) -> ::nitroglycerin::update::Counter<#DL, #D, #output #ty_generics, #ty>
+                }
+            })
+            .collect();
+
+        let impls = counters.iter().zip(&signatures).map(|(Column { name, .. }, signature)| {
+            quote! {
+                #signature {
+                    ::nitroglycerin::update::Counter::new(self, #name, by)
+                }
+            }
+        });
+
+        tokens.extend(quote! {
+            #[doc = #trait_doc]
+            #vis trait #counters_trait<#DL, #D: #DL + ?Sized> {
+                #( #signatures; )*
+            }
+
+            impl #impl_generics #counters_trait<#DL, #D> for ::nitroglycerin::key::Expr<#DL, #D, ::nitroglycerin::dynamodb::UpdateItemInput, #output #ty_generics> #where_clause {
+                #( #impls )*
+            }
+        });
+    }
+}
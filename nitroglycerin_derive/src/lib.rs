@@ -66,18 +66,29 @@
 
 use std::convert::TryFrom;
 
-use attr::field;
+use attr::{container, field};
 use proc_macro::TokenStream;
+use quote::quote;
 use syn::{parse_macro_input, parse_quote, spanned::Spanned, DeriveInput};
 
 mod attr;
+mod case;
 mod convert;
 mod key;
 mod query;
 mod iter;
+mod template;
 
 trait Builder {
     fn parse(vis: syn::Visibility, name: syn::Ident, generics: syn::Generics, attrs: Vec<syn::Attribute>, fields: syn::FieldsNamed) -> syn::Result<proc_macro2::TokenStream>;
+
+    fn parse_unnamed(_vis: syn::Visibility, _name: syn::Ident, _generics: syn::Generics, _attrs: Vec<syn::Attribute>, fields: syn::FieldsUnnamed) -> syn::Result<proc_macro2::TokenStream> {
+        Err(syn::Error::new(fields.span(), "tuple structs not supported"))
+    }
+
+    fn parse_enum(_vis: syn::Visibility, name: syn::Ident, _generics: syn::Generics, _attrs: Vec<syn::Attribute>, _data: syn::DataEnum) -> syn::Result<proc_macro2::TokenStream> {
+        Err(syn::Error::new(name.span(), "enums not supported"))
+    }
 }
 
 fn derive<P: Builder>(input: TokenStream) -> TokenStream {
@@ -91,10 +102,16 @@ fn derive<P: Builder>(input: TokenStream) -> TokenStream {
                 Ok(t) => t,
                 Err(e) => e.to_compile_error(),
             },
-            syn::Fields::Unnamed(_) => syn::Error::new(span, "tuple structs not supported").into_compile_error(),
+            syn::Fields::Unnamed(fields) => match P::parse_unnamed(vis, ident, generics, attrs, fields) {
+                Ok(t) => t,
+                Err(e) => e.to_compile_error(),
+            },
             syn::Fields::Unit => syn::Error::new(span, "unit structs not supported").into_compile_error(),
         },
-        syn::Data::Enum(_) => syn::Error::new(span, "enums not supported").into_compile_error(),
+        syn::Data::Enum(data) => match P::parse_enum(vis, ident, generics, attrs, data) {
+            Ok(t) => t,
+            Err(e) => e.to_compile_error(),
+        },
         syn::Data::Union(_) => syn::Error::new(span, "unions not supported").into_compile_error(),
     }
     .into()
@@ -113,6 +130,13 @@ pub fn derive_query(input: TokenStream) -> TokenStream {
 }
 
 /// Implement `Into<Attributes>` and `TryFrom<Attributes>`
+///
+/// Tuple structs are stored as a list attribute value. Single field structs marked
+/// with `#[nitro(transparent)]` are stored exactly like the type they wrap.
+///
+/// Enums marked with `#[nitro(tag = "...")]` hold one entity type per variant, each wrapping
+/// a type that implements `Attributes`. The variant name (or its `rename`) is stored in the tag attribute
+/// and used to pick the variant when decoding
 #[proc_macro_derive(Attributes, attributes(nitro))]
 pub fn derive_convert(input: TokenStream) -> TokenStream {
     derive::<convert::Builder>(input)
@@ -160,21 +184,165 @@ impl TryFrom<syn::Field> for NamedField {
     }
 }
 
+#[derive(Clone)]
+struct UnnamedField {
+    pub attrs: field::Attr,
+    pub index: syn::Index,
+    pub ty: syn::Type,
+}
+
+impl TryFrom<(usize, syn::Field)> for UnnamedField {
+    type Error = syn::Error;
+    fn try_from((index, field): (usize, syn::Field)) -> syn::Result<Self> {
+        let syn::Field { attrs, ty, .. } = field;
+        let attrs = field::Attr::parse_attrs(attrs)?;
+        if attrs.rename.is_some() || attrs.partition_key.is_some() || attrs.sort_key.is_some() || attrs.skip.is_some() || attrs.default.is_some() || attrs.skip_if_none.is_some() || attrs.flatten.is_some() || attrs.version.is_some() || attrs.created_at.is_some() || attrs.updated_at.is_some() || attrs.ttl.is_some() {
+            return Err(syn::Error::new(ty.span(), "only `with` is supported on tuple struct fields"));
+        }
+        Ok(Self { attrs, index: syn::Index::from(index), ty })
+    }
+}
+
+/// A variant of a tagged entity enum, such as `User(User)`
+struct EntityVariant {
+    pub ident: syn::Ident,
+    pub tag: String,
+    pub ty: syn::Type,
+}
+
+impl TryFrom<syn::Variant> for EntityVariant {
+    type Error = syn::Error;
+    fn try_from(variant: syn::Variant) -> syn::Result<Self> {
+        let syn::Variant { attrs, ident, fields, .. } = variant;
+        let attrs = field::Attr::parse_attrs(attrs)?;
+        if attrs.partition_key.is_some() || attrs.sort_key.is_some() || attrs.with.is_some() || attrs.skip.is_some() || attrs.default.is_some() || attrs.skip_if_none.is_some() || attrs.flatten.is_some() || attrs.version.is_some() || attrs.created_at.is_some() || attrs.updated_at.is_some() || attrs.ttl.is_some() {
+            return Err(syn::Error::new(ident.span(), "only `rename` is supported on enum variants"));
+        }
+        let ty = match fields {
+            syn::Fields::Unnamed(f) if f.unnamed.len() == 1 => f.unnamed.into_iter().next().unwrap().ty,
+            f => return Err(syn::Error::new(f.span(), "enum variants must wrap exactly one entity type")),
+        };
+        let tag = attrs.rename.map_or_else(|| ident.to_string(), |r| r.value());
+        Ok(Self { ident, tag, ty })
+    }
+}
+
+/// path to the function that converts a value of `ty` into an attribute value
+fn into_av_fn(ty: &syn::Type, with: Option<&syn::Path>) -> proc_macro2::TokenStream {
+    with.map_or_else(|| quote! { <#ty as ::nitroglycerin::convert::IntoAttributeValue>::into_av }, |with| quote! { #with::into_av })
+}
+
+/// path to the function that converts an attribute value into a value of `ty`
+fn from_av_fn(ty: &syn::Type, with: Option<&syn::Path>) -> proc_macro2::TokenStream {
+    with.map_or_else(|| quote! { <#ty as ::nitroglycerin::convert::FromAttributeValue>::try_from_av }, |with| quote! { #with::try_from_av })
+}
+
 #[derive(Clone)]
 struct Column {
     pub ident: syn::Ident,
     pub name: String,
     pub ty: syn::Type,
     pub with: Option<syn::Path>,
+    pub skip: bool,
+    pub default: Option<field::DefaultValue>,
+    pub skip_if_none: bool,
+    pub flatten: bool,
+    pub marker: Option<Marker>,
+}
+
+/// Fields with a special meaning to the put and update requests
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Version,
+    CreatedAt,
+    UpdatedAt,
+    Ttl,
 }
 
-impl From<NamedField> for Column {
-    fn from(f: NamedField) -> Self {
+impl Column {
+    fn new(f: NamedField, container: &container::Attr) -> Self {
+        let name = match (&f.attrs.rename, container.rename_all) {
+            (Some(rename), _) => rename.value(),
+            (None, Some(rule)) => rule.apply(&f.name.to_string()),
+            (None, None) => f.name.to_string(),
+        };
+        let marker = match (f.attrs.version, f.attrs.created_at, f.attrs.updated_at, f.attrs.ttl) {
+            (Some(()), _, _, _) => Some(Marker::Version),
+            (_, Some(()), _, _) => Some(Marker::CreatedAt),
+            (_, _, Some(()), _) => Some(Marker::UpdatedAt),
+            (_, _, _, Some(())) => Some(Marker::Ttl),
+            _ => None,
+        };
+        // dynamodb only understands ttl values in seconds, whatever the usual conversion of the type is
+        let with = if marker == Some(Marker::Ttl) { Some(parse_quote!(::nitroglycerin::ttl)) } else { f.attrs.with };
         Self {
-            name: f.attrs.rename.as_ref().map_or_else(|| f.name.to_string(), syn::LitStr::value),
+            name,
             ident: f.name,
             ty: f.ty,
-            with: f.attrs.with,
+            with,
+            skip: f.attrs.skip.is_some(),
+            default: f.attrs.default,
+            skip_if_none: f.attrs.skip_if_none.is_some() || container.skip_if_none.is_some(),
+            flatten: f.attrs.flatten.is_some(),
+            marker,
         }
     }
 }
+
+/// The value of a partition or sort key, either stored in a single field or composed out of several
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+enum KeyColumn {
+    Field(Column),
+    Template(template::Template),
+}
+
+impl KeyColumn {
+    /// Name of the key attribute
+    fn name(&self) -> &str {
+        match self {
+            Self::Field(c) => &c.name,
+            Self::Template(t) => &t.name,
+        }
+    }
+}
+
+/// The key templates declared on the struct, as `(partition_key, sort_key)`
+fn templates(attrs: &container::Attr, columns: &[Column]) -> syn::Result<(Option<template::Template>, Option<template::Template>)> {
+    let name = |name: &Option<syn::LitStr>, default: &str| name.as_ref().map_or_else(|| default.to_owned(), syn::LitStr::value);
+
+    let partition_key = attrs.partition_key.as_ref().map(|t| template::Template::parse(name(&attrs.partition_key_name, "PK"), t, columns)).transpose()?;
+    let sort_key = attrs.sort_key.as_ref().map(|t| template::Template::parse(name(&attrs.sort_key_name, "SK"), t, columns)).transpose()?;
+
+    if let (Some(p), Some(s)) = (&partition_key, &sort_key) {
+        if let Some(c) = s.components.iter().find(|c| p.contains(&c.ident)) {
+            return Err(syn::Error::new_spanned(&c.ident, "field cannot be used in both the partition key and sort key templates"));
+        }
+    }
+
+    Ok((partition_key, sort_key))
+}
+
+/// Find the partition key and sort key of a table, as `(partition_key, sort_key)`
+fn key_columns(fields: &[NamedField], attrs: &container::Attr) -> syn::Result<(KeyColumn, Option<KeyColumn>)> {
+    let columns: Vec<_> = fields.iter().map(|f| Column::new(f.clone(), attrs)).collect();
+    let (partition_template, sort_template) = templates(attrs, &columns)?;
+
+    let partition_field = fields.iter().find_map(|f| f.attrs.partition_key.map(|()| Column::new(f.clone(), attrs)));
+    let sort_field = fields.iter().find_map(|f| f.attrs.sort_key.map(|()| Column::new(f.clone(), attrs)));
+
+    let partition_key = match (partition_field, partition_template) {
+        (Some(c), None) => KeyColumn::Field(c),
+        (None, Some(t)) => KeyColumn::Template(t),
+        (Some(c), Some(_)) => return Err(syn::Error::new_spanned(c.ident, "partition key cannot be both a field and a template")),
+        (None, None) => return Err(syn::Error::new(proc_macro2::Span::call_site(), "table needs a partition key")),
+    };
+    let sort_key = match (sort_field, sort_template) {
+        (Some(c), None) => Some(KeyColumn::Field(c)),
+        (None, Some(t)) => Some(KeyColumn::Template(t)),
+        (Some(c), Some(_)) => return Err(syn::Error::new_spanned(c.ident, "sort key cannot be both a field and a template")),
+        (None, None) => None,
+    };
+
+    Ok((partition_key, sort_key))
+}
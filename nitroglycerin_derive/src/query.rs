@@ -1,26 +1,27 @@
 use std::convert::TryFrom;
 
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::TokenStream;
 use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{parse_quote, Generics, Ident, Type, Visibility};
 
-use crate::{Column, NamedField, D, DL};
+use crate::{
+    attr::container,
+    key_columns,
+    template::{Chain, Template},
+    Column, KeyColumn, NamedField, D, DL,
+};
 
-impl<'a> crate::Builder for Builder<'a> {
-    fn parse(vis: Visibility, name: syn::Ident, generics: syn::Generics, _attrs: Vec<syn::Attribute>, fields: syn::FieldsNamed) -> syn::Result<TokenStream> {
+impl crate::Builder for Builder<'_> {
+    fn parse(vis: Visibility, name: syn::Ident, generics: syn::Generics, attrs: Vec<syn::Attribute>, fields: syn::FieldsNamed) -> syn::Result<TokenStream> {
+        let attrs = container::Attr::parse_attrs(attrs)?;
         let fields: Vec<_> = fields.named.into_iter().map(NamedField::try_from).collect::<syn::Result<_>>()?;
-    
-        let partition_key: Column = fields
-            .iter()
-            .find_map(|f| f.attrs.partition_key.map(|_| f.clone().into()))
-            .ok_or_else(|| syn::Error::new(Span::call_site(), "table needs a partition key"))?;
-    
-        let sort_key = fields.iter().find_map(|f| f.attrs.sort_key.map(|_| f.clone().into()));
-    
-        Ok(Builder::new(&vis, &name, &generics, partition_key, sort_key).to_token_stream())
+        let (partition_key, sort_key) = key_columns(&fields, &attrs)?;
+
+        Ok(Builder::new(&vis, &name, &generics, partition_key, sort_key, attrs.item).to_token_stream())
     }
 }
 
+#[allow(clippy::struct_field_names)]
 pub struct Builder<'a> {
     trait_builder: TraitBuilder<'a>,
     query_builder1: Builder1<'a>,
@@ -28,16 +29,16 @@ pub struct Builder<'a> {
 }
 
 impl<'a> Builder<'a> {
-    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, partition_key: Column, sort_key: Option<Column>) -> Self {
+    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, partition_key: KeyColumn, sort_key: Option<KeyColumn>, item: Option<Type>) -> Self {
         Self {
             trait_builder: TraitBuilder::new(output, generics),
             query_builder1: Builder1::new(vis, output, generics, partition_key),
-            query_builder2: Builder2::new(vis, output, generics, sort_key),
+            query_builder2: Builder2::new(vis, output, generics, sort_key, item),
         }
     }
 }
 
-impl<'a> ToTokens for Builder<'a> {
+impl ToTokens for Builder<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             trait_builder,
@@ -70,7 +71,7 @@ impl<'a> TraitBuilder<'a> {
     }
 }
 
-impl<'a> ToTokens for TraitBuilder<'a> {
+impl ToTokens for TraitBuilder<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self { output, generics, new_generics } = self;
         let builder = format_ident!("{}QueryBuilder", output);
@@ -96,11 +97,11 @@ struct Builder1<'a> {
     generics: &'a Generics,
     new_generics: Generics,
     phantom_data: Type,
-    partition_key: Column,
+    partition_key: KeyColumn,
 }
 
 impl<'a> Builder1<'a> {
-    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, partition_key: Column) -> Self {
+    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, partition_key: KeyColumn) -> Self {
         let mut new_generics = generics.clone();
 
         let tys = new_generics.type_params().map(|tp| &tp.ident);
@@ -126,7 +127,7 @@ impl<'a> Builder1<'a> {
     }
 }
 
-impl<'a> ToTokens for Builder1<'a> {
+impl ToTokens for Builder1<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             vis,
@@ -139,12 +140,10 @@ impl<'a> ToTokens for Builder1<'a> {
         let builder = format_ident!("{}QueryBuilder", output);
         let builder_p = format_ident!("{}Partition", builder);
 
-        let Column { ident, name, ty, .. } = partition_key;
-
         let (impl_generics, ty_generics, where_clause) = new_generics.split_for_impl();
         let (_, ty_generics2, _) = generics.split_for_impl();
 
-        let type_doc = format!("part one of the query builder chain for {}", output);
+        let type_doc = format!("part one of the query builder chain for {output}");
 
         tokens.extend(quote! {
             #[doc = #type_doc]
@@ -154,7 +153,32 @@ impl<'a> ToTokens for Builder1<'a> {
             }
         });
 
-        let fn_doc = format!("set the value of the sort key ({})", ident);
+        let Column { ident, name, ty, .. } = match partition_key {
+            KeyColumn::Field(column) => column,
+            KeyColumn::Template(template) => {
+                let name = &template.name;
+                let value = template.compose(&quote! { parts });
+                let chain = Chain {
+                    vis,
+                    start: &builder,
+                    generics: new_generics,
+                    phantom_data,
+                    state: vec![],
+                    components: &template.components,
+                    output: quote! { #builder_p #ty_generics },
+                    bounds: quote! { #output #ty_generics2: ::nitroglycerin::TableIndex, },
+                    finish: quote! {
+                        let input = ::nitroglycerin::query::new_input::<#output #ty_generics2, _>(#name, #value);
+                        #builder_p::new(client, input)
+                    },
+                    partial: quote! {},
+                    doc: "partition key",
+                };
+                return chain.to_tokens(tokens);
+            }
+        };
+
+        let fn_doc = format!("set the value of the sort key ({ident})");
 
         tokens.extend(quote_spanned! { ident.span() =>
             impl #impl_generics #builder #ty_generics #where_clause {
@@ -182,11 +206,12 @@ struct Builder2<'a> {
     generics: &'a Generics,
     new_generics: Generics,
     phantom_data: Type,
-    sort_key: Option<Column>,
+    sort_key: Option<KeyColumn>,
+    item: Option<Type>,
 }
 
 impl<'a> Builder2<'a> {
-    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, sort_key: Option<Column>) -> Self {
+    fn new(vis: &'a Visibility, output: &'a Ident, generics: &'a Generics, sort_key: Option<KeyColumn>, item: Option<Type>) -> Self {
         let mut new_generics = generics.clone();
 
         let tys = new_generics.type_params().map(|tp| &tp.ident);
@@ -207,11 +232,53 @@ impl<'a> Builder2<'a> {
             new_generics,
             phantom_data,
             sort_key,
+            item,
         }
     }
 }
 
-impl<'a> ToTokens for Builder2<'a> {
+impl Builder2<'_> {
+    /// builders that set each component of a templated sort key in turn, querying by the key prefix or the full key
+    fn template_chain(&self, template: &Template, item: &TokenStream, tokens: &mut TokenStream) {
+        let Self {
+            vis,
+            output,
+            generics,
+            new_generics,
+            phantom_data,
+            ..
+        } = self;
+        let builder_p = format_ident!("{}QueryBuilderPartition", output);
+        let (_, ty_generics2, _) = generics.split_for_impl();
+
+        let name = &template.name;
+        let prefix = template.compose(&quote! { parts });
+        let expr = quote! { ::nitroglycerin::query::Expr<#DL, #D, #output #ty_generics2, #item> };
+        let sort = quote! { ::nitroglycerin::query::BuilderSort::<#DL, #D, ::std::string::String, #output #ty_generics2, #item>::new(client, input, #name) };
+        let chain = Chain {
+            vis,
+            start: &builder_p,
+            generics: new_generics,
+            phantom_data,
+            state: vec![(format_ident!("input"), parse_quote! { ::nitroglycerin::dynamodb::QueryInput })],
+            components: &template.components,
+            output: expr.clone(),
+            bounds: quote! {},
+            finish: quote! { #sort.equal(#prefix) },
+            partial: quote! {
+                /// query for sort keys that begin with the components set so far
+                #vis fn begins_with(self) -> #expr {
+                    let Self { client, parts, input, _phantom } = self;
+                    #sort.begins_with(#prefix)
+                }
+            },
+            doc: "sort key",
+        };
+        chain.to_tokens(tokens);
+    }
+}
+
+impl ToTokens for Builder2<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             vis,
@@ -220,6 +287,7 @@ impl<'a> ToTokens for Builder2<'a> {
             generics,
             new_generics,
             phantom_data,
+            item,
         } = self;
 
         let builder = format_ident!("{}QueryBuilder", output);
@@ -228,10 +296,24 @@ impl<'a> ToTokens for Builder2<'a> {
         let (impl_generics, ty_generics, where_clause) = new_generics.split_for_impl();
         let (_, ty_generics2, _) = generics.split_for_impl();
 
-        let type_doc = format!("part two of the query builder chain for {}", output);
+        let type_doc = format!("part two of the query builder chain for {output}");
+
+        // queries return the index type itself, unless it declares another `item` type such as an entity enum
+        let item = item.as_ref().map_or_else(|| quote! { #output #ty_generics2 }, ToTokens::to_token_stream);
+
+        // templated sort keys can be queried by their components, or by their raw value for any other prefix
+        if let Some(KeyColumn::Template(template)) = sort_key {
+            self.template_chain(template, &item, tokens);
+        }
+
+        // the raw value of a templated sort key can be queried with any condition
+        let sort_key: Option<(Ident, String, Type)> = sort_key.as_ref().map(|sort_key| match sort_key {
+            KeyColumn::Field(Column { ident, name, ty, .. }) => (ident.clone(), name.clone(), ty.clone()),
+            KeyColumn::Template(template) => (format_ident!("sort_key"), template.name.clone(), parse_quote! { ::std::string::String }),
+        });
 
         match sort_key {
-            Some(Column { ident, name, ty, .. }) => {
+            Some((ident, name, ty)) => {
                 tokens.extend(quote! {
                     #[doc = #type_doc]
                     #vis struct #builder_p #impl_generics {
@@ -245,29 +327,64 @@ impl<'a> ToTokens for Builder2<'a> {
                             Self { client, input, _phantom: ::std::marker::PhantomData }
                         }
 
-                        #vis fn consistent_read(self) -> ::nitroglycerin::query::Expr<#DL, #D, #output #ty_generics2> {
+                        #vis fn consistent_read(self) -> ::nitroglycerin::query::Expr<#DL, #D, #output #ty_generics2, #item> {
+                            let Self { client, input, _phantom } = self;
+                            ::nitroglycerin::query::Expr::<#DL, #D, #output #ty_generics2, #item>::new(client, input).consistent_read()
+                        }
+
+                        #vis fn return_consumed_capacity(self, return_consumed_capacity: ::nitroglycerin::meta::ReturnConsumedCapacity) -> ::nitroglycerin::query::Expr<#DL, #D, #output #ty_generics2, #item> {
+                            let Self { client, input, _phantom } = self;
+                            ::nitroglycerin::query::Expr::<#DL, #D, #output #ty_generics2, #item>::new(client, input).return_consumed_capacity(return_consumed_capacity)
+                        }
+
+                        #vis fn skip_invalid(self) -> ::nitroglycerin::query::Expr<#DL, #D, #output #ty_generics2, #item> {
+                            let Self { client, input, _phantom } = self;
+                            ::nitroglycerin::query::Expr::<#DL, #D, #output #ty_generics2, #item>::new(client, input).skip_invalid()
+                        }
+
+                        #vis async fn execute(self) -> ::std::result::Result<::std::vec::Vec<#item>, ::nitroglycerin::DynamoError<::nitroglycerin::dynamodb::QueryError>>
+                        where
+                            #D: ::nitroglycerin::dynamodb::DynamoDb,
+                            &#DL #D: ::std::marker::Send,
+                            #item: ::std::convert::TryFrom<::nitroglycerin::Attributes, Error = ::nitroglycerin::AttributeError> + ::std::marker::Send,
+                        {
+                            let Self { client, input, _phantom } = self;
+                            ::nitroglycerin::query::Expr::<#DL, #D, #output #ty_generics2, #item>::new(client, input).execute().await
+                        }
+
+                        #vis async fn execute_with_meta(self) -> ::std::result::Result<(::std::vec::Vec<#item>, ::nitroglycerin::meta::Meta), ::nitroglycerin::DynamoError<::nitroglycerin::dynamodb::QueryError>>
+                        where
+                            #D: ::nitroglycerin::dynamodb::DynamoDb,
+                            &#DL #D: ::std::marker::Send,
+                            #item: ::std::convert::TryFrom<::nitroglycerin::Attributes, Error = ::nitroglycerin::AttributeError> + ::std::marker::Send,
+                        {
                             let Self { client, input, _phantom } = self;
-                            ::nitroglycerin::query::Expr::new(client, input).consistent_read()
+                            ::nitroglycerin::query::Expr::<#DL, #D, #output #ty_generics2, #item>::new(client, input).execute_with_meta().await
                         }
 
-                        #vis async fn execute(self) -> ::std::result::Result<::std::vec::Vec<#output #ty_generics2>, ::nitroglycerin::DynamoError<::nitroglycerin::dynamodb::QueryError>>
+                        #vis async fn execute_collect_invalid(
+                            self,
+                        ) -> ::std::result::Result<
+                            (::std::vec::Vec<#item>, ::std::vec::Vec<::nitroglycerin::query::InvalidItem>, ::nitroglycerin::meta::Meta),
+                            ::nitroglycerin::DynamoError<::nitroglycerin::dynamodb::QueryError>,
+                        >
                         where
                             #D: ::nitroglycerin::dynamodb::DynamoDb,
                             &#DL #D: ::std::marker::Send,
-                            #output #ty_generics2: ::std::convert::TryFrom<::nitroglycerin::Attributes, Error = ::nitroglycerin::AttributeError> + ::std::marker::Send,
+                            #item: ::std::convert::TryFrom<::nitroglycerin::Attributes, Error = ::nitroglycerin::AttributeError> + ::std::marker::Send,
                         {
                             let Self { client, input, _phantom } = self;
-                            ::nitroglycerin::query::Expr::new(client, input).execute().await
+                            ::nitroglycerin::query::Expr::<#DL, #D, #output #ty_generics2, #item>::new(client, input).execute_collect_invalid().await
                         }
                     }
                 });
 
-                let fn_doc = format!("set the value of the sort key ({})", ident);
+                let fn_doc = format!("set the value of the sort key ({ident})");
 
                 tokens.extend(quote_spanned! { ident.span() =>
                     impl #impl_generics #builder_p #ty_generics #where_clause {
                         #[doc = #fn_doc]
-                        #vis fn #ident(self) -> ::nitroglycerin::query::BuilderSort<#DL, #D, #ty, #output #ty_generics2> {
+                        #vis fn #ident(self) -> ::nitroglycerin::query::BuilderSort<#DL, #D, #ty, #output #ty_generics2, #item> {
                             let Self { client, input, _phantom } = self;
                             ::nitroglycerin::query::BuilderSort::new(client, input, #name)
                         }
@@ -276,7 +393,7 @@ impl<'a> ToTokens for Builder2<'a> {
             }
             None => tokens.extend(quote! {
                 #[doc = #type_doc]
-                #vis type #builder_p #ty_generics = ::nitroglycerin::query::Expr<#DL, #D, #output #ty_generics2>;
+                #vis type #builder_p #ty_generics = ::nitroglycerin::query::Expr<#DL, #D, #output #ty_generics2, #item>;
             }),
         }
     }
@@ -0,0 +1,205 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{Ident, Type, Visibility};
+
+use crate::{case::RenameRule, Column, D, DL};
+
+/// A composite key value built out of other fields, such as `USER#{user_id}`
+#[derive(Clone)]
+pub struct Template {
+    pub name: String,
+    pub source: String,
+    /// text surrounding each component. Always one longer than `components`
+    pub literals: Vec<String>,
+    pub components: Vec<Column>,
+}
+
+impl Template {
+    pub fn parse(name: String, template: &syn::LitStr, columns: &[Column]) -> syn::Result<Self> {
+        let value = template.value();
+        let mut literals = vec![String::new()];
+        let mut components = Vec::new();
+
+        let mut rest = value.as_str();
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}').ok_or_else(|| syn::Error::new_spanned(template, "unclosed `{` in key template"))? + start;
+            let field = &rest[start + 1..end];
+
+            let column = columns
+                .iter()
+                .find(|c| c.ident == field)
+                .ok_or_else(|| syn::Error::new_spanned(template, format!("unknown field `{field}` in key template")))?;
+            if column.with.is_some() || column.skip || column.default.is_some() || column.flatten || column.marker.is_some() {
+                return Err(syn::Error::new_spanned(&column.ident, "key template components cannot have any other parameters"));
+            }
+            if !components.is_empty() && start == 0 {
+                return Err(syn::Error::new_spanned(template, "key template components must be separated by some text"));
+            }
+
+            literals.last_mut().unwrap().push_str(&rest[..start]);
+            literals.push(String::new());
+            components.push(column.clone());
+            rest = &rest[end + 1..];
+        }
+        literals.last_mut().unwrap().push_str(rest);
+
+        if components.is_empty() {
+            return Err(syn::Error::new_spanned(template, "key template needs at least one field component"));
+        }
+
+        Ok(Self {
+            name,
+            source: value,
+            literals,
+            components,
+        })
+    }
+
+    pub fn contains(&self, ident: &Ident) -> bool {
+        self.components.iter().any(|c| &c.ident == ident)
+    }
+
+    /// expression that joins the strings in `parts` into the key value
+    pub fn compose(&self, parts: &TokenStream) -> TokenStream {
+        let literals = &self.literals;
+        quote! { ::nitroglycerin::key::compose(&[ #( #literals ),* ], &#parts) }
+    }
+
+    /// statements that extract the key from `a`, binding each component field to an `Option`.
+    /// Errors are recorded in `errors` rather than returned, like those of any other field
+    pub fn decompose(&self, errors: &Ident, index: usize) -> TokenStream {
+        let Self { name, source, literals, components } = self;
+        let value = Ident::new(&format!("key{index}"), Span::mixed_site());
+        let parts = Ident::new(&format!("key{index}_parts"), Span::mixed_site());
+        let parses = components.iter().enumerate().map(|(i, c)| {
+            let Column { ident, ty, .. } = c;
+            quote_spanned! { ident.span() =>
+                let #ident = #parts.as_ref().and_then(|parts| #errors.check(
+                    <#ty as ::std::str::FromStr>::from_str(parts[#i]).map_err(|e| ::nitroglycerin::AttributeError::ParseError(::std::boxed::Box::new(e)).in_field(#name))
+                ));
+            }
+        });
+        quote! {
+            let #value: ::std::option::Option<::std::string::String> = #errors.check(
+                a.remove(#name)
+                    .ok_or_else(|| ::nitroglycerin::AttributeError::MissingField(#name.to_owned()))
+                    .and_then(|av| ::nitroglycerin::convert::FromAttributeValue::try_from_av(av).map_err(|e| e.in_field(#name)))
+            );
+            let #parts = #value.as_deref().and_then(|value| #errors.check(
+                ::nitroglycerin::key::decompose(value, &[ #( #literals ),* ]).ok_or_else(|| {
+                    ::nitroglycerin::AttributeError::ParseError(::std::format!("{:?} does not match the key template {:?}", value, #source).into()).in_field(#name)
+                })
+            ));
+            #( #parses )*
+        }
+    }
+}
+
+/// Generates a chain of builder types, one setter per template component.
+///
+/// The first setter is implemented on `start`, which must already exist with `client` and `_phantom` fields
+/// along with any fields in `state`. The last setter evaluates `finish`, which can use `client`, `parts` and the `state` fields.
+/// `partial` holds extra methods for the builders in between, which have those same fields
+pub struct Chain<'a> {
+    pub vis: &'a Visibility,
+    pub start: &'a Ident,
+    pub generics: &'a syn::Generics,
+    pub phantom_data: &'a Type,
+    pub state: Vec<(Ident, Type)>,
+    pub components: &'a [Column],
+    pub output: TokenStream,
+    pub bounds: TokenStream,
+    pub finish: TokenStream,
+    pub partial: TokenStream,
+    pub doc: &'a str,
+}
+
+impl Chain<'_> {
+    pub fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            vis,
+            start,
+            generics,
+            phantom_data,
+            state,
+            components,
+            output,
+            bounds,
+            finish,
+            partial,
+            doc,
+        } = self;
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let (state_idents, state_tys): (Vec<_>, Vec<_>) = state.iter().cloned().unzip();
+
+        let mut current = (*start).clone();
+        for (i, column) in components.iter().enumerate() {
+            let Column { ident, ty, .. } = column;
+            let len = components.len();
+
+            let take_parts = if i == 0 {
+                quote! { let Self { client, #( #state_idents, )* _phantom } = self; let mut parts = ::std::vec::Vec::with_capacity(#len); }
+            } else {
+                quote! { let Self { client, mut parts, #( #state_idents, )* _phantom } = self; }
+            };
+
+            let fn_doc = format!("set the value of {ident} in the {doc}");
+
+            if i + 1 == len {
+                tokens.extend(quote_spanned! { ident.span() =>
+                    impl #impl_generics #current #ty_generics #where_clause {
+                        #[doc = #fn_doc]
+                        #vis fn #ident(self, #ident: impl ::std::convert::Into<#ty>) -> #output
+                        where
+                            #ty: ::std::fmt::Display,
+                            #bounds
+                        {
+                            let #ident: #ty = #ident.into();
+                            #take_parts
+                            parts.push(#ident.to_string());
+                            #finish
+                        }
+                    }
+                });
+            } else {
+                let next = format_ident!("{}With{}", start, RenameRule::Pascal.apply(&ident.to_string()));
+                let type_doc = format!("{doc} after setting {ident}");
+
+                tokens.extend(quote! {
+                    #[doc = #type_doc]
+                    #vis struct #next #impl_generics {
+                        client: &#DL #D,
+                        parts: ::std::vec::Vec<::std::string::String>,
+                        #( #state_idents: #state_tys, )*
+                        _phantom: ::std::marker::PhantomData<#phantom_data>,
+                    }
+                });
+
+                tokens.extend(quote_spanned! { ident.span() =>
+                    impl #impl_generics #current #ty_generics #where_clause {
+                        #[doc = #fn_doc]
+                        #vis fn #ident(self, #ident: impl ::std::convert::Into<#ty>) -> #next #ty_generics
+                        where
+                            #ty: ::std::fmt::Display,
+                        {
+                            let #ident: #ty = #ident.into();
+                            #take_parts
+                            parts.push(#ident.to_string());
+                            #next { client, parts, #( #state_idents, )* _phantom }
+                        }
+                    }
+                });
+
+                if !partial.is_empty() {
+                    tokens.extend(quote! {
+                        impl #impl_generics #next #ty_generics #where_clause {
+                            #partial
+                        }
+                    });
+                }
+
+                current = next;
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
 #![cfg(test)]
+// every mocked response closure returns a `RusotoError`, which clippy considers too large
+#![allow(clippy::result_large_err)]
 
 #[macro_use]
 extern crate async_trait;
@@ -41,6 +43,230 @@ impl Table for ExampleTable2 {
     }
 }
 
+#[derive(Debug, PartialEq, Attributes)]
+#[nitro(transparent)]
+struct EmployeeId(String);
+
+#[derive(Debug, PartialEq, Attributes)]
+struct Point(i32, i32);
+
+#[derive(Debug, PartialEq, Attributes, Key, Query)]
+struct ExampleTable3 {
+    #[nitro(partition_key)]
+    pub id: EmployeeId,
+
+    pub location: Point,
+}
+
+impl Table for ExampleTable3 {
+    fn table_name() -> String {
+        "ExampleTable3Name".into()
+    }
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+struct Defaults {
+    pub id: String,
+
+    #[nitro(skip)]
+    pub cached: Option<String>,
+
+    #[nitro(default)]
+    pub tags: Vec<String>,
+
+    #[nitro(default = default_level)]
+    pub level: u8,
+}
+
+fn default_level() -> u8 {
+    3
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+#[nitro(skip_if_none)]
+struct Sparse {
+    #[nitro(partition_key)]
+    pub id: String,
+
+    pub gsi_key: Option<String>,
+}
+
+impl Table for Sparse {
+    fn table_name() -> String {
+        "SparseName".into()
+    }
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+struct Audit {
+    pub created_by: String,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+struct Audited {
+    pub id: String,
+
+    #[nitro(flatten)]
+    pub audit: Audit,
+}
+
+#[derive(Debug, PartialEq, Attributes, Key, Query)]
+#[nitro(rename_all = "camelCase")]
+struct CamelTable {
+    #[nitro(partition_key)]
+    pub user_id: String,
+
+    #[nitro(sort_key)]
+    pub created_at: i64,
+
+    #[nitro(rename = "Extra")]
+    pub extra_value: String,
+}
+
+impl Table for CamelTable {
+    fn table_name() -> String {
+        "CamelTableName".into()
+    }
+}
+
+#[derive(Debug, PartialEq, Attributes, Key, Query)]
+#[nitro(partition_key = "USER#{user_id}", sort_key = "ORDER#{date}#{order_id}")]
+struct Order {
+    pub user_id: String,
+    pub date: String,
+    pub order_id: u32,
+    pub total: u32,
+}
+
+impl Table for Order {
+    fn table_name() -> String {
+        "SingleTableName".into()
+    }
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+#[nitro(partition_key = "USER#{user_id}", sort_key = "PROFILE#{name}")]
+struct Profile {
+    pub user_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+#[nitro(tag = "_type")]
+enum UserItem {
+    Profile(Profile),
+    #[nitro(rename = "ORDER")]
+    Order(Order),
+}
+
+#[derive(Query)]
+#[nitro(partition_key = "USER#{user_id}", item = UserItem)]
+#[allow(dead_code)]
+struct UserPartition {
+    pub user_id: String,
+}
+
+impl Table for UserPartition {
+    fn table_name() -> String {
+        "SingleTableName".into()
+    }
+}
+
+// the items of the partition have different sort keys, so there is no `Key` derive to declare them
+impl nitroglycerin::key::PrimaryKey for UserPartition {
+    fn partition_key_name() -> &'static str {
+        "PK"
+    }
+
+    fn sort_key_name() -> Option<&'static str> {
+        Some("SK")
+    }
+}
+
+#[derive(Debug, PartialEq, Attributes, Key)]
+struct Account {
+    #[nitro(partition_key)]
+    pub id: String,
+
+    #[nitro(version)]
+    pub version: u32,
+
+    pub balance: i64,
+}
+
+impl Table for Account {
+    fn table_name() -> String {
+        "AccountName".into()
+    }
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+struct Note {
+    pub id: String,
+
+    #[nitro(created_at)]
+    pub created_at: i64,
+
+    #[nitro(updated_at)]
+    pub updated_at: Option<u64>,
+}
+
+impl Table for Note {
+    fn table_name() -> String {
+        "NoteName".into()
+    }
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+struct Stamped {
+    pub id: String,
+
+    #[nitro(flatten)]
+    pub stamps: Stamps,
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+struct Stamps {
+    #[nitro(created_at)]
+    pub created_at: i64,
+
+    #[nitro(updated_at)]
+    pub updated_at: i64,
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+struct Session {
+    pub id: String,
+
+    #[nitro(ttl, rename = "expiresAt", skip_if_none)]
+    pub expires_at: Option<std::time::SystemTime>,
+}
+
+impl Table for Session {
+    fn table_name() -> String {
+        "SessionName".into()
+    }
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+#[nitro(tag = "_type")]
+enum Ledger {
+    Account(Account),
+    Note(Note),
+}
+
+impl Table for Ledger {
+    fn table_name() -> String {
+        "LedgerName".into()
+    }
+}
+
+#[derive(Debug, PartialEq, Attributes)]
+struct AccountBalance {
+    pub balance: i64,
+}
+
 macro_rules! av {
     ($t:ident: $v:expr) => {
         AttributeValue {
@@ -312,3 +538,1505 @@ async fn test_query_no_sort() {
     ]);
 }
 
+
+#[tokio::test]
+async fn test_get_newtype() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_get_item()
+        .with(eq(GetItemInput {
+            key: m!(
+                "id" => av!(s: "emp_1"),
+            ),
+            table_name: "ExampleTable3Name".into(),
+            ..Default::default()
+        }))
+        .returning(|_| {
+            Ok(GetItemOutput {
+                item: Some(m!(
+                    "id" => av!(s: "emp_1"),
+                    "location" => av!(
+                        l: vec![
+                            av!(n: "3"),
+                            av!(n: "-4"),
+                        ]
+                    ),
+                )),
+                ..Default::default()
+            })
+        });
+
+    let output = client.get::<ExampleTable3>().id(EmployeeId("emp_1".into())).execute().await.unwrap();
+    assert_eq!(
+        output,
+        Some(ExampleTable3 {
+            id: EmployeeId("emp_1".into()),
+            location: Point(3, -4),
+        })
+    );
+}
+
+#[test]
+fn test_tuple_struct_length() {
+    use nitroglycerin::{convert::FromAttributeValue, AttributeError};
+
+    let av = av!(l: vec![av!(n: "1"), av!(n: "2"), av!(n: "3")]);
+    assert!(matches!(Point::try_from_av(av), Err(AttributeError::IncorrectLength { expected: 2, found: 3 })));
+
+    let av = av!(l: vec![av!(n: "1")]);
+    assert!(matches!(Point::try_from_av(av), Err(AttributeError::IncorrectLength { expected: 2, found: 1 })));
+}
+
+#[tokio::test]
+async fn test_parse_error_path() {
+    use nitroglycerin::{convert::AttributeType, AttributeError, DynamoError};
+
+    let mut client = MockDynamoDbClient::new();
+    client.expect_get_item().returning(|_| {
+        Ok(GetItemOutput {
+            item: Some(m!(
+                "id" => av!(s: "emp_1"),
+                "location" => av!(l: vec![av!(n: "3"), av!(s: "north")]),
+            )),
+            ..Default::default()
+        })
+    });
+
+    let err = client.get::<ExampleTable3>().id(EmployeeId("emp_1".into())).execute().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "could not parse item {id=\"emp_1\"} in table ExampleTable3Name: at location[1]: incorrect type: expected N, found S"
+    );
+
+    let DynamoError::ItemParseError { table_name, key, source } = err else { panic!("expected an item parse error") };
+    assert_eq!(table_name, "ExampleTable3Name");
+    assert_eq!(key, m!("id" => av!(s: "emp_1"),));
+    assert_eq!(source.path().unwrap().to_string(), "location[1]");
+    assert!(matches!(
+        source.root(),
+        AttributeError::IncorrectType {
+            expected: AttributeType::N,
+            found: AttributeType::S
+        }
+    ));
+}
+
+#[tokio::test]
+async fn test_query_newtype() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_query()
+        .with(eq(QueryInput {
+            key_condition_expression: Some("#0 = :0".into()),
+            expression_attribute_names: Some(m! {
+                "#0" => "id",
+            }),
+            expression_attribute_values: Some(m! {
+                ":0" => av!(s: "emp_1"),
+            }),
+            table_name: "ExampleTable3Name".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Ok(QueryOutput { items: Some(vec![]), ..Default::default() }));
+
+    let output = client.query::<ExampleTable3>().id(EmployeeId("emp_1".into())).execute().await.unwrap();
+    assert_eq!(output, vec![]);
+}
+
+#[test]
+fn test_defaults() {
+    use std::convert::TryFrom;
+
+    let item: Attributes = m!(
+        "id" => av!(s: "foo"),
+        "cached" => av!(s: "ignored"),
+    );
+    let defaults = Defaults::try_from(item).unwrap();
+    assert_eq!(defaults, Defaults {
+        id: "foo".into(),
+        cached: None,
+        tags: vec![],
+        level: 3,
+    });
+
+    let item: Attributes = Defaults {
+        id: "foo".into(),
+        cached: Some("bar".into()),
+        tags: vec!["baz".into()],
+        level: 1,
+    }
+    .into();
+    assert_eq!(item, m!(
+        "id" => av!(s: "foo"),
+        "tags" => av!(l: vec![av!(s: "baz")]),
+        "level" => av!(n: "1"),
+    ));
+}
+
+#[tokio::test]
+async fn test_put_skip_if_none() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_put_item()
+        .with(eq(PutItemInput {
+            item: m!(
+                "id" => av!(s: "foo"),
+            ),
+            table_name: "SparseName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Ok(PutItemOutput::default()));
+
+    client.put(Sparse { id: "foo".into(), gsi_key: None }).execute().await.unwrap();
+}
+
+#[test]
+fn test_extract_skip_if_none() {
+    use std::convert::TryFrom;
+
+    let item: Attributes = m!(
+        "id" => av!(s: "foo"),
+    );
+    assert_eq!(Sparse::try_from(item).unwrap(), Sparse { id: "foo".into(), gsi_key: None });
+
+    let item: Attributes = m!(
+        "gsi_key" => av!(s: "bar"),
+    );
+    assert!(matches!(Sparse::try_from(item), Err(nitroglycerin::AttributeError::MissingField(f)) if f == "id"));
+}
+
+#[test]
+fn test_flatten() {
+    use std::convert::TryFrom;
+
+    let audited = Audited {
+        id: "foo".into(),
+        audit: Audit {
+            created_by: "conrad".into(),
+            updated_at: 1626900000,
+        },
+    };
+
+    let item: Attributes = m!(
+        "id" => av!(s: "foo"),
+        "created_by" => av!(s: "conrad"),
+        "updated_at" => av!(n: "1626900000"),
+    );
+
+    assert_eq!(Attributes::from(Audited::try_from(item.clone()).unwrap()), item);
+    assert_eq!(Audited::try_from(item).unwrap(), audited);
+}
+
+#[tokio::test]
+async fn test_rename_all() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_query()
+        .with(eq(QueryInput {
+            key_condition_expression: Some("#0 = :0 AND #1 >= :1".into()),
+            expression_attribute_names: Some(m! {
+                "#0" => "userId",
+                "#1" => "createdAt",
+            }),
+            expression_attribute_values: Some(m! {
+                ":0" => av!(s: "foo"),
+                ":1" => av!(n: "42"),
+            }),
+            table_name: "CamelTableName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| {
+            Ok(QueryOutput {
+                items: Some(vec![m!(
+                    "userId" => av!(s: "foo"),
+                    "createdAt" => av!(n: "43"),
+                    "Extra" => av!(s: "bar"),
+                )]),
+                ..Default::default()
+            })
+        });
+    client
+        .expect_get_item()
+        .with(eq(GetItemInput {
+            key: m!(
+                "userId" => av!(s: "foo"),
+                "createdAt" => av!(n: "43"),
+            ),
+            table_name: "CamelTableName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Ok(GetItemOutput::default()));
+
+    let output = client.query::<CamelTable>().user_id("foo").created_at().greater_than_or_equal(42).execute().await.unwrap();
+    assert_eq!(output, vec![CamelTable {
+        user_id: "foo".into(),
+        created_at: 43,
+        extra_value: "bar".into(),
+    }]);
+
+    let output = client.get::<CamelTable>().user_id("foo").created_at(43).execute().await.unwrap();
+    assert_eq!(output, None);
+}
+
+#[tokio::test]
+async fn test_get_key_template() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_get_item()
+        .with(eq(GetItemInput {
+            key: m!(
+                "PK" => av!(s: "USER#conrad"),
+                "SK" => av!(s: "ORDER#2021-07-22#5"),
+            ),
+            table_name: "SingleTableName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| {
+            Ok(GetItemOutput {
+                item: Some(m!(
+                    "PK" => av!(s: "USER#conrad"),
+                    "SK" => av!(s: "ORDER#2021-07-22#5"),
+                    "total" => av!(n: "100"),
+                )),
+                ..Default::default()
+            })
+        });
+
+    let output = client.get::<Order>().user_id("conrad").date("2021-07-22").order_id(5u32).execute().await.unwrap();
+    let order = Order {
+        user_id: "conrad".into(),
+        date: "2021-07-22".into(),
+        order_id: 5,
+        total: 100,
+    };
+    assert_eq!(output, Some(order));
+}
+
+#[tokio::test]
+async fn test_query_key_template() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_query()
+        .with(eq(QueryInput {
+            key_condition_expression: Some("#0 = :0 AND begins_with(#1, :1)".into()),
+            expression_attribute_names: Some(m! {
+                "#0" => "PK",
+                "#1" => "SK",
+            }),
+            expression_attribute_values: Some(m! {
+                ":0" => av!(s: "USER#conrad"),
+                ":1" => av!(s: "ORDER#2021-07"),
+            }),
+            table_name: "SingleTableName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| {
+            Ok(QueryOutput {
+                items: Some(vec![m!(
+                    "PK" => av!(s: "USER#conrad"),
+                    "SK" => av!(s: "ORDER#2021-07-22#bad"),
+                    "total" => av!(n: "100"),
+                )]),
+                ..Default::default()
+            })
+        });
+
+    let output = client.query::<Order>().user_id("conrad").sort_key().begins_with("ORDER#2021-07").execute().await;
+    let Err(nitroglycerin::DynamoError::ItemParseError { key, source, .. }) = output else { panic!("expected an item parse error") };
+    assert_eq!(key.len(), 2);
+    assert_eq!(source.path().unwrap().to_string(), "SK");
+    assert!(matches!(source.root(), nitroglycerin::AttributeError::ParseError(_)));
+}
+
+#[tokio::test]
+async fn test_query_key_template_components() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_query()
+        .with(eq(QueryInput {
+            key_condition_expression: Some("#0 = :0 AND begins_with(#1, :1)".into()),
+            expression_attribute_names: Some(m! {
+                "#0" => "PK",
+                "#1" => "SK",
+            }),
+            expression_attribute_values: Some(m! {
+                ":0" => av!(s: "USER#conrad"),
+                ":1" => av!(s: "ORDER#2021-07-22#"),
+            }),
+            table_name: "SingleTableName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Ok(QueryOutput::default()));
+    client
+        .expect_query()
+        .with(eq(QueryInput {
+            key_condition_expression: Some("#0 = :0 AND #1 = :1".into()),
+            expression_attribute_names: Some(m! {
+                "#0" => "PK",
+                "#1" => "SK",
+            }),
+            expression_attribute_values: Some(m! {
+                ":0" => av!(s: "USER#conrad"),
+                ":1" => av!(s: "ORDER#2021-07-22#5"),
+            }),
+            table_name: "SingleTableName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Ok(QueryOutput::default()));
+
+    let output = client.query::<Order>().user_id("conrad").date("2021-07-22").begins_with().execute().await.unwrap();
+    assert_eq!(output, vec![]);
+    let output = client.query::<Order>().user_id("conrad").date("2021-07-22").order_id(5u32).execute().await.unwrap();
+    assert_eq!(output, vec![]);
+}
+
+#[test]
+fn test_key_template_separator_in_component() {
+    use std::convert::TryFrom;
+
+    let order = Order {
+        user_id: "con#rad".into(),
+        date: "2021#07#22".into(),
+        order_id: 5,
+        total: 100,
+    };
+    let item: Attributes = order.into();
+    assert_eq!(item["SK"], av!(s: "ORDER#2021#07#22#5"));
+    let order = Order::try_from(item).unwrap();
+    assert_eq!((order.user_id.as_str(), order.date.as_str(), order.order_id), ("con#rad", "2021#07#22", 5));
+}
+
+#[test]
+fn test_key_template_errors() {
+    use std::convert::TryFrom;
+
+    let item: Attributes = m!(
+        "PK" => av!(s: "USER#conrad"),
+        "SK" => av!(s: "ORDER#2021-07-22#five"),
+        "total" => av!(s: "100"),
+    );
+    let err = Order::try_from(item).unwrap_err();
+    assert_eq!(err.errors().len(), 2);
+
+    let item: Attributes = m!(
+        "PK" => av!(s: "CUSTOMER#conrad"),
+        "total" => av!(n: "100"),
+    );
+    let err = Order::try_from(item).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "2 fields could not be parsed: at PK: could not parse value: \"CUSTOMER#conrad\" does not match the key template \"USER#{user_id}\"; missing field SK"
+    );
+}
+
+#[test]
+fn test_put_key_template() {
+    let item: Attributes = Order {
+        user_id: "conrad".into(),
+        date: "2021-07-22".into(),
+        order_id: 5,
+        total: 100,
+    }
+    .into();
+    assert_eq!(item, m!(
+        "PK" => av!(s: "USER#conrad"),
+        "SK" => av!(s: "ORDER#2021-07-22#5"),
+        "total" => av!(n: "100"),
+    ));
+}
+
+#[tokio::test]
+async fn test_query_entities() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_query()
+        .with(eq(QueryInput {
+            key_condition_expression: Some("#0 = :0".into()),
+            expression_attribute_names: Some(m! {
+                "#0" => "PK",
+            }),
+            expression_attribute_values: Some(m! {
+                ":0" => av!(s: "USER#conrad"),
+            }),
+            table_name: "SingleTableName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| {
+            Ok(QueryOutput {
+                items: Some(vec![
+                    m!(
+                        "_type" => av!(s: "Profile"),
+                        "PK" => av!(s: "USER#conrad"),
+                        "SK" => av!(s: "PROFILE#Conrad"),
+                    ),
+                    m!(
+                        "_type" => av!(s: "ORDER"),
+                        "PK" => av!(s: "USER#conrad"),
+                        "SK" => av!(s: "ORDER#2021-07-22#5"),
+                        "total" => av!(n: "100"),
+                    ),
+                ]),
+                ..Default::default()
+            })
+        });
+
+    let output = client.query::<UserPartition>().user_id("conrad").execute().await.unwrap();
+    assert_eq!(
+        output,
+        vec![
+            UserItem::Profile(Profile {
+                user_id: "conrad".into(),
+                name: "Conrad".into(),
+            }),
+            UserItem::Order(Order {
+                user_id: "conrad".into(),
+                date: "2021-07-22".into(),
+                order_id: 5,
+                total: 100,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_entity_tag() {
+    let item: Attributes = UserItem::Profile(Profile {
+        user_id: "conrad".into(),
+        name: "Conrad".into(),
+    })
+    .into();
+    assert_eq!(item, m!(
+        "_type" => av!(s: "Profile"),
+        "PK" => av!(s: "USER#conrad"),
+        "SK" => av!(s: "PROFILE#Conrad"),
+    ));
+
+    let unknown = <UserItem as std::convert::TryFrom<Attributes>>::try_from(m!("_type" => av!(s: "INVOICE"),));
+    assert!(matches!(unknown, Err(nitroglycerin::AttributeError::UnknownType(t)) if t == "INVOICE"));
+}
+
+#[tokio::test]
+async fn test_put_version() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_put_item()
+        .with(eq(PutItemInput {
+            item: m!(
+                "id" => av!(s: "acc_1"),
+                "version" => av!(n: "4"),
+                "balance" => av!(n: "100"),
+            ),
+            condition_expression: Some("#v = :v".into()),
+            expression_attribute_names: Some(m! {
+                "#v" => "version",
+            }),
+            expression_attribute_values: Some(m! {
+                ":v" => av!(n: "3"),
+            }),
+            table_name: "AccountName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Err(rusoto_core::RusotoError::Service(PutItemError::ConditionalCheckFailed("conditional request failed".into()))));
+
+    let output = client.put(Account { id: "acc_1".into(), version: 3, balance: 100 }).execute().await;
+    assert!(matches!(output, Err(nitroglycerin::DynamoError::VersionMismatch)));
+}
+
+#[tokio::test]
+async fn test_put_version_new() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_put_item()
+        .with(eq(PutItemInput {
+            item: m!(
+                "id" => av!(s: "acc_1"),
+                "version" => av!(n: "1"),
+                "balance" => av!(n: "0"),
+            ),
+            condition_expression: Some("attribute_not_exists(#v)".into()),
+            expression_attribute_names: Some(m! {
+                "#v" => "version",
+            }),
+            table_name: "AccountName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Ok(PutItemOutput::default()));
+
+    client.put(Account { id: "acc_1".into(), version: 0, balance: 0 }).execute().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_put_version_overflow() {
+    let client = MockDynamoDbClient::new();
+
+    let output = client.put(Account { id: "acc_1".into(), version: u32::MAX, balance: 0 }).execute().await;
+    assert!(matches!(output, Err(nitroglycerin::DynamoError::VersionOverflow)));
+}
+
+#[tokio::test]
+async fn test_put_hand_written() {
+    use nitroglycerin::{timestamp::Timestamped, version::Versioned};
+
+    struct Raw(Attributes);
+
+    impl From<Raw> for Attributes {
+        fn from(raw: Raw) -> Self {
+            raw.0
+        }
+    }
+
+    impl Table for Raw {
+        fn table_name() -> String {
+            "AccountName".into()
+        }
+    }
+
+    impl Versioned for Raw {}
+    impl Timestamped for Raw {}
+
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_put_item()
+        .with(eq(PutItemInput {
+            item: m!(
+                "id" => av!(s: "acc_1"),
+                "version" => av!(n: "3"),
+                "balance" => av!(n: "100"),
+            ),
+            table_name: "AccountName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Ok(PutItemOutput::default()));
+
+    // hand written items opt out of the version check
+    let item = Account { id: "acc_1".into(), version: 3, balance: 100 }.into();
+    client.put(Raw(item)).execute().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_put_tagged_version() {
+    use nitroglycerin::version::Versioned;
+
+    let item = Ledger::Account(Account { id: "acc_1".into(), version: 3, balance: 100 });
+    assert_eq!(item.item_version_attribute(), Some("version"));
+    assert_eq!(item.next_version(), Some(4));
+    assert_eq!(Ledger::Note(Note { id: "foo".into(), created_at: 0, updated_at: None }).item_version_attribute(), None);
+
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_put_item()
+        .with(eq(PutItemInput {
+            item: m!(
+                "_type" => av!(s: "Account"),
+                "id" => av!(s: "acc_1"),
+                "version" => av!(n: "4"),
+                "balance" => av!(n: "100"),
+            ),
+            condition_expression: Some("#v = :v".into()),
+            expression_attribute_names: Some(m! {
+                "#v" => "version",
+            }),
+            expression_attribute_values: Some(m! {
+                ":v" => av!(n: "3"),
+            }),
+            table_name: "LedgerName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Ok(PutItemOutput::default()));
+
+    client.put(item).execute().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_delete_version() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_delete_item()
+        .with(eq(DeleteItemInput {
+            key: m!(
+                "id" => av!(s: "acc_1"),
+            ),
+            condition_expression: Some("#v = :v".into()),
+            expression_attribute_names: Some(m! {
+                "#v" => "version",
+            }),
+            expression_attribute_values: Some(m! {
+                ":v" => av!(n: "2"),
+            }),
+            table_name: "AccountName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| Ok(DeleteItemOutput::default()));
+
+    client.delete::<Account>().id("acc_1").if_version(2).execute().await.unwrap();
+}
+
+#[test]
+fn test_touch_timestamps() {
+    use nitroglycerin::timestamp::Timestamped;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let now = UNIX_EPOCH + Duration::from_secs(1_626_900_000);
+
+    let mut note = Note { id: "foo".into(), created_at: 0, updated_at: None };
+    note.touch(now);
+    assert_eq!(note, Note { id: "foo".into(), created_at: 1_626_900_000, updated_at: Some(1_626_900_000) });
+
+    let mut note = Note { id: "foo".into(), created_at: 1_600_000_000, updated_at: Some(1_600_000_000) };
+    note.touch(now);
+    assert_eq!(note, Note { id: "foo".into(), created_at: 1_600_000_000, updated_at: Some(1_626_900_000) });
+
+    assert_eq!(Note::created_at(now), Some(("created_at", av!(n: "1626900000"))));
+    assert_eq!(Note::updated_at(now), Some(("updated_at", av!(n: "1626900000"))));
+}
+
+#[tokio::test]
+async fn test_put_timestamps() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_put_item()
+        .withf(|input| {
+            let updated_at = input.item["updated_at"].n.as_deref().unwrap().parse::<u64>().unwrap();
+            input.table_name == "NoteName" && input.item["created_at"] == av!(n: "1600000000") && updated_at > 1_600_000_000
+        })
+        .returning(|_| Ok(PutItemOutput::default()));
+
+    client.put(Note { id: "foo".into(), created_at: 1_600_000_000, updated_at: None }).execute().await.unwrap();
+}
+
+#[test]
+fn test_touch_flatten() {
+    use nitroglycerin::timestamp::Timestamped;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let now = UNIX_EPOCH + Duration::from_secs(1_626_900_000);
+    let mut item = Stamped { id: "foo".into(), stamps: Stamps { created_at: 1_600_000_000, updated_at: 0 } };
+    item.touch(now);
+    assert_eq!(item.stamps, Stamps { created_at: 1_600_000_000, updated_at: 1_626_900_000 });
+
+    assert_eq!(Stamped::created_at(now), Some(("created_at", av!(n: "1626900000"))));
+    assert_eq!(Stamped::updated_at(now), Some(("updated_at", av!(n: "1626900000"))));
+}
+
+#[test]
+fn test_touch_tagged() {
+    use nitroglycerin::timestamp::Timestamped;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let now = UNIX_EPOCH + Duration::from_secs(1_626_900_000);
+    let mut item = Ledger::Note(Note { id: "foo".into(), created_at: 0, updated_at: None });
+    item.touch(now);
+    assert_eq!(item, Ledger::Note(Note { id: "foo".into(), created_at: 1_626_900_000, updated_at: Some(1_626_900_000) }));
+
+    assert_eq!(item.item_created_at(now), Some(("created_at", av!(n: "1626900000"))));
+    assert_eq!(item.item_updated_at(now), Some(("updated_at", av!(n: "1626900000"))));
+    let account = Ledger::Account(Account { id: "acc_1".into(), version: 3, balance: 100 });
+    assert_eq!(account.item_created_at(now), None);
+}
+
+#[test]
+fn test_ttl() {
+    use std::{
+        convert::TryFrom,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    let session = Session { id: "foo".into(), expires_at: Some(UNIX_EPOCH + Duration::from_secs(1_626_900_000)) };
+    let item: Attributes = session.into();
+    assert_eq!(item, m!(
+        "id" => av!(s: "foo"),
+        "expiresAt" => av!(n: "1626900000"),
+    ));
+    assert_eq!(Session::try_from(item).unwrap().expires_at, Some(UNIX_EPOCH + Duration::from_secs(1_626_900_000)));
+
+    let item: Attributes = Session { id: "foo".into(), expires_at: None }.into();
+    assert_eq!(item, m!(
+        "id" => av!(s: "foo"),
+    ));
+    assert_eq!(Session::try_from(item).unwrap().expires_at, None);
+}
+
+#[test]
+fn test_ttl_out_of_range() {
+    assert_eq!(nitroglycerin::ttl::try_from_av::<u64>(av!(n: "1626900000")).unwrap(), 1_626_900_000);
+    assert!(nitroglycerin::ttl::try_from_av::<u64>(av!(n: "-1")).is_err());
+}
+
+#[tokio::test]
+async fn test_enable_ttl() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_update_time_to_live()
+        .with(eq(UpdateTimeToLiveInput {
+            table_name: "SessionName".into(),
+            time_to_live_specification: TimeToLiveSpecification {
+                attribute_name: "expiresAt".into(),
+                enabled: true,
+            },
+        }))
+        .returning(|_| Ok(UpdateTimeToLiveOutput::default()));
+
+    client.enable_ttl::<Session>().execute().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_increment() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_update_item()
+        .with(eq(UpdateItemInput {
+            key: m!(
+                "id" => av!(s: "acc_1"),
+            ),
+            update_expression: Some("ADD #0 :0, #1 :1".into()),
+            expression_attribute_names: Some(m! {
+                "#0" => "balance",
+                "#1" => "version",
+            }),
+            expression_attribute_values: Some(m! {
+                ":0" => av!(n: "5"),
+                ":1" => av!(n: "1"),
+            }),
+            return_values: Some("UPDATED_NEW".into()),
+            table_name: "AccountName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| {
+            Ok(UpdateItemOutput {
+                attributes: Some(m!(
+                    "balance" => av!(n: "105"),
+                    "version" => av!(n: "4"),
+                )),
+                ..Default::default()
+            })
+        });
+
+    let balance = client.increment::<Account>().id("acc_1").balance(5).execute().await.unwrap();
+    assert_eq!(balance, 105);
+}
+
+#[tokio::test]
+async fn test_put_return_all_old() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_put_item()
+        .withf(|input| input.return_values.as_deref() == Some("ALL_OLD"))
+        .returning(|_| {
+            Ok(PutItemOutput {
+                attributes: Some(m!(
+                    "id" => av!(s: "acc_1"),
+                    "version" => av!(n: "3"),
+                    "balance" => av!(n: "50"),
+                )),
+                ..Default::default()
+            })
+        });
+
+    let old = client.put(Account { id: "acc_1".into(), version: 3, balance: 100 }).return_all_old().execute().await.unwrap();
+    assert_eq!(old, Some(Account { id: "acc_1".into(), version: 3, balance: 50 }));
+}
+
+#[tokio::test]
+async fn test_put_return_all_old_parse_error() {
+    let mut client = MockDynamoDbClient::new();
+    client.expect_put_item().returning(|_| {
+        Ok(PutItemOutput {
+            attributes: Some(m!(
+                "id" => av!(s: "acc_1"),
+                "version" => av!(n: "3"),
+                "balance" => av!(s: "fifty"),
+            )),
+            ..Default::default()
+        })
+    });
+
+    let err = client.put(Account { id: "acc_1".into(), version: 3, balance: 100 }).return_all_old().execute().await.unwrap_err();
+    let nitroglycerin::DynamoError::ItemParseError { table_name, key, .. } = err else { panic!("expected an item parse error") };
+    assert_eq!(table_name, "AccountName");
+    assert_eq!(key.get("id"), Some(&av!(s: "acc_1")));
+}
+
+#[tokio::test]
+async fn test_increment_return_updated_old() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_update_item()
+        .withf(|input| input.return_values.as_deref() == Some("UPDATED_OLD"))
+        .returning(|_| {
+            Ok(UpdateItemOutput {
+                attributes: Some(m!(
+                    "balance" => av!(n: "100"),
+                    "version" => av!(n: "3"),
+                )),
+                ..Default::default()
+            })
+        });
+
+    let old = client.increment::<Account>().id("acc_1").balance(5).return_updated_old::<AccountBalance>().execute().await.unwrap();
+    assert_eq!(old, Some(AccountBalance { balance: 100 }));
+}
+
+#[tokio::test]
+async fn test_get_with_meta() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_get_item()
+        .with(eq(GetItemInput {
+            key: m!(
+                "id" => av!(s: "acc_1"),
+            ),
+            return_consumed_capacity: Some("TOTAL".into()),
+            table_name: "AccountName".into(),
+            ..Default::default()
+        }))
+        .returning(|_| {
+            Ok(GetItemOutput {
+                consumed_capacity: Some(ConsumedCapacity {
+                    capacity_units: Some(0.5),
+                    table_name: Some("AccountName".into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        });
+
+    let (output, meta) = client
+        .get::<Account>()
+        .id("acc_1")
+        .return_consumed_capacity(nitroglycerin::meta::ReturnConsumedCapacity::Total)
+        .execute_with_meta()
+        .await
+        .unwrap();
+    assert_eq!(output, None);
+    assert!((meta.capacity_units() - 0.5).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn test_query_with_meta() {
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_query()
+        .withf(|input| input.return_consumed_capacity.as_deref() == Some("INDEXES"))
+        .returning(|_| {
+            Ok(QueryOutput {
+                items: Some(vec![]),
+                count: Some(0),
+                scanned_count: Some(3),
+                ..Default::default()
+            })
+        });
+
+    let (output, meta) = client
+        .query::<UserPartition>()
+        .user_id("conrad")
+        .return_consumed_capacity(nitroglycerin::meta::ReturnConsumedCapacity::Indexes)
+        .execute_with_meta()
+        .await
+        .unwrap();
+    assert_eq!(output, vec![]);
+    assert_eq!((meta.count, meta.scanned_count), (Some(0), Some(3)));
+}
+
+type ObservedRequest = (nitroglycerin::observe::RequestInfo, Option<i64>, Option<i64>, bool);
+
+#[test]
+fn test_collect_field_errors() {
+    use std::convert::TryFrom;
+
+    use nitroglycerin::AttributeError;
+
+    let item: Attributes = m!(
+        "id" => av!(n: "1"),
+        "extra_values" => av!(l: vec![av!(s: "foo"), av!(n: "2")]),
+    );
+    let err = ExampleTable1::try_from(item).unwrap_err();
+    assert_eq!(err.errors().len(), 3);
+    assert_eq!(
+        err.to_string(),
+        "3 fields could not be parsed: at id: incorrect type: expected S, found N; missing field range; at extra_values[1]: incorrect type: expected S, found N"
+    );
+
+    let item: Attributes = m!(
+        "id" => av!(s: "foo"),
+        "extra_values" => av!(l: vec![]),
+    );
+    let err = ExampleTable1::try_from(item).unwrap_err();
+    assert!(matches!(err, AttributeError::MissingField(field) if field == "range"));
+}
+
+fn query_with_invalid_items(client: &mut MockDynamoDbClient) {
+    client.expect_query().returning(|_| {
+        Ok(QueryOutput {
+            items: Some(vec![
+                m!(
+                    "id" => av!(s: "foo"),
+                    "range" => av!(n: "1"),
+                    "extra_values" => av!(l: vec![]),
+                ),
+                m!(
+                    "id" => av!(s: "foo"),
+                    "range" => av!(n: "2"),
+                ),
+                m!(
+                    "id" => av!(s: "foo"),
+                    "range" => av!(n: "3"),
+                    "extra_values" => av!(l: vec![]),
+                ),
+            ]),
+            count: Some(3),
+            ..Default::default()
+        })
+    });
+}
+
+#[tokio::test]
+async fn test_query_skip_invalid() {
+    let mut client = MockDynamoDbClient::new();
+    query_with_invalid_items(&mut client);
+    let err = client.query::<ExampleTable1>().partition("foo").execute().await.unwrap_err();
+    let nitroglycerin::DynamoError::ItemParseError { key, .. } = err else { panic!("expected an item parse error") };
+    // the sort key is reported even though the query does not name it
+    assert_eq!(key, m!(
+        "id" => av!(s: "foo"),
+        "range" => av!(n: "2"),
+    ));
+
+    let mut client = MockDynamoDbClient::new();
+    query_with_invalid_items(&mut client);
+    let output = client.query::<ExampleTable1>().partition("foo").skip_invalid().execute().await.unwrap();
+    assert_eq!(output.iter().map(|item| item.sort).collect::<Vec<_>>(), vec![1, 3]);
+}
+
+#[tokio::test]
+async fn test_query_collect_invalid() {
+    let mut client = MockDynamoDbClient::new();
+    query_with_invalid_items(&mut client);
+
+    let (items, invalid, meta) = client.query::<ExampleTable1>().partition("foo").sort().greater_than(0).execute_collect_invalid().await.unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(meta.count, Some(3));
+    assert_eq!(invalid.len(), 1);
+    assert_eq!(invalid[0].key, m!(
+        "id" => av!(s: "foo"),
+        "range" => av!(n: "2"),
+    ));
+    assert_eq!(invalid[0].error.to_string(), "missing field extra_values");
+}
+
+#[derive(Default)]
+struct RecordingObserver {
+    requests: std::sync::Mutex<Vec<ObservedRequest>>,
+}
+
+impl nitroglycerin::observe::RequestObserver for RecordingObserver {
+    fn after(&self, request: &nitroglycerin::observe::RequestInfo, outcome: &nitroglycerin::observe::Outcome<'_>) {
+        let record = (request.clone(), outcome.count, outcome.scanned_count, outcome.error.is_some());
+        self.requests.lock().unwrap().push(record);
+    }
+}
+
+#[tokio::test]
+async fn test_observer() {
+    use nitroglycerin::{observe::RequestInfo, operation::Operation};
+
+    let mut client = MockDynamoDbClient::new();
+    client.expect_get_item().returning(|_| Err(rusoto_core::RusotoError::Service(GetItemError::ResourceNotFound("AccountName".into()))));
+    client.expect_query().returning(|_| {
+        Ok(QueryOutput {
+            items: Some(vec![]),
+            count: Some(0),
+            scanned_count: Some(2),
+            ..Default::default()
+        })
+    });
+
+    let client = client.with_observer(RecordingObserver::default());
+    client.get::<Account>().id("acc_1").execute().await.unwrap_err();
+    client.query::<UserPartition>().user_id("conrad").execute().await.unwrap();
+
+    let requests = client.observer().requests.lock().unwrap();
+    assert_eq!(
+        *requests,
+        vec![
+            (
+                RequestInfo {
+                    operation: Operation::GetItem,
+                    table_name: Some("AccountName".into()),
+                    index_name: None,
+                },
+                None,
+                None,
+                true,
+            ),
+            (
+                RequestInfo {
+                    operation: Operation::Query,
+                    table_name: Some("SingleTableName".into()),
+                    index_name: None,
+                },
+                Some(0),
+                Some(2),
+                false,
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_retry() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use nitroglycerin::retry::RetryPolicy;
+
+    let calls = std::sync::Arc::new(AtomicUsize::new(0));
+    let mut client = MockDynamoDbClient::new();
+    let counter = calls.clone();
+    client.expect_get_item().returning(move |_| match counter.fetch_add(1, Ordering::SeqCst) {
+        0 | 1 => Err(rusoto_core::RusotoError::Service(GetItemError::ProvisionedThroughputExceeded("slow down".into()))),
+        _ => Ok(GetItemOutput::default()),
+    });
+
+    let policy = RetryPolicy::default().base_delay(std::time::Duration::from_millis(1)).jitter(false);
+    let client = client.with_retry(policy);
+    let output = client.get::<Account>().id("acc_1").execute().await.unwrap();
+    assert_eq!(output, None);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_retry_attempts() {
+    use nitroglycerin::retry::RetryPolicy;
+
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_get_item()
+        .times(3)
+        .returning(|_| Err(rusoto_core::RusotoError::Service(GetItemError::RequestLimitExceeded("slow down".into()))));
+    client
+        .expect_query()
+        .times(1)
+        .returning(|_| Err(rusoto_core::RusotoError::Service(QueryError::ResourceNotFound("SingleTableName".into()))));
+
+    let policy = RetryPolicy::default().max_attempts(3).base_delay(std::time::Duration::from_millis(1));
+    let err = policy.run(|| client.get::<Account>().id("acc_1").execute()).await.unwrap_err();
+    assert_eq!(err.attempts, 3);
+
+    let err = policy.run(|| client.query::<UserPartition>().user_id("conrad").execute()).await.unwrap_err();
+    assert_eq!(err.attempts, 1);
+}
+
+#[tokio::test]
+async fn test_retry_not_idempotent() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use nitroglycerin::retry::RetryPolicy;
+
+    let calls = std::sync::Arc::new(AtomicUsize::new(0));
+    let mut client = MockDynamoDbClient::new();
+    let counter = calls.clone();
+    client.expect_update_item().returning(move |_| match counter.fetch_add(1, Ordering::SeqCst) {
+        0 => Err(rusoto_core::RusotoError::Service(UpdateItemError::ProvisionedThroughputExceeded("slow down".into()))),
+        _ => Err(rusoto_core::RusotoError::Service(UpdateItemError::InternalServerError("oops".into()))),
+    });
+
+    let policy = RetryPolicy::default().base_delay(std::time::Duration::from_millis(1)).jitter(false);
+    let client = client.with_retry(policy);
+    let err = client.increment::<Account>().id("acc_1").balance(5).execute().await.unwrap_err();
+    assert_eq!(err.kind(), nitroglycerin::error::ErrorKind::Service);
+    // the throttled attempt is retried, the internal server error could have applied the update already
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_retry_conditional_put() {
+    use nitroglycerin::retry::RetryPolicy;
+
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_put_item()
+        .times(1)
+        .returning(|_| Err(rusoto_core::RusotoError::Service(PutItemError::InternalServerError("oops".into()))));
+
+    // the version condition would fail if the first attempt had been applied
+    let policy = RetryPolicy::default().base_delay(std::time::Duration::from_millis(1));
+    let client = client.with_retry(policy);
+    let err = client.put(Account { id: "acc_1".into(), version: 3, balance: 100 }).execute().await.unwrap_err();
+    assert_eq!(err.kind(), nitroglycerin::error::ErrorKind::Service);
+}
+
+#[tokio::test]
+async fn test_retry_unprocessed() {
+    use nitroglycerin::retry::RetryPolicy;
+
+    let key = |id: &str| m!("id" => av!(s: id),);
+    let mut client = MockDynamoDbClient::new();
+    client.expect_batch_get_item().returning(move |input| {
+        let mut keys = input.request_items["AccountName"].keys.clone();
+        let item = keys.remove(0);
+        Ok(BatchGetItemOutput {
+            responses: Some(m!("AccountName" => vec![item],)),
+            unprocessed_keys: Some(m!("AccountName" => KeysAndAttributes { keys, ..Default::default() },)),
+            ..Default::default()
+        })
+    });
+
+    let input = BatchGetItemInput {
+        request_items: m!("AccountName" => KeysAndAttributes { keys: vec![key("acc_1"), key("acc_2"), key("acc_3")], ..Default::default() },),
+        ..Default::default()
+    };
+
+    let policy = RetryPolicy::default().base_delay(std::time::Duration::from_millis(1)).jitter(false);
+    let retry = client.with_retry(policy.clone());
+    let output = rusoto_dynamodb::DynamoDb::batch_get_item(&retry, input.clone()).await.unwrap();
+    assert_eq!(output.responses.unwrap()["AccountName"], vec![key("acc_1"), key("acc_2"), key("acc_3")]);
+    assert!(output.unprocessed_keys.unwrap()["AccountName"].keys.is_empty());
+
+    // once the policy gives up, the keys still unprocessed are handed back
+    let (client, _) = retry.into_parts();
+    let retry = client.with_retry(policy.max_attempts(2));
+    let output = rusoto_dynamodb::DynamoDb::batch_get_item(&retry, input).await.unwrap();
+    assert_eq!(output.responses.unwrap()["AccountName"], vec![key("acc_1"), key("acc_2")]);
+    assert_eq!(output.unprocessed_keys.unwrap()["AccountName"].keys, vec![key("acc_3")]);
+}
+
+#[tokio::test]
+async fn test_rate_limit() {
+    use nitroglycerin::limit::RateLimit;
+
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_get_item()
+        .withf(|input| input.return_consumed_capacity.as_deref() == Some("INDEXES"))
+        .times(2)
+        .returning(|_| {
+            Ok(GetItemOutput {
+                consumed_capacity: Some(ConsumedCapacity {
+                    capacity_units: Some(105.0),
+                    table_name: Some("AccountName".into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        });
+
+    let client = client.with_rate_limit::<Account>(RateLimit::default().read(100.0));
+    let start = std::time::Instant::now();
+    client.get::<Account>().id("acc_1").execute().await.unwrap();
+    assert!(start.elapsed() < std::time::Duration::from_millis(40));
+
+    // the first request left the bucket 5 units in debt, which takes 50ms to pay back
+    client.get::<Account>().id("acc_1").execute().await.unwrap();
+    assert!(start.elapsed() >= std::time::Duration::from_millis(40));
+}
+
+#[tokio::test]
+async fn test_error_kind() {
+    use nitroglycerin::error::ErrorKind;
+    use rusoto_core::{request::HttpDispatchError, RusotoError};
+
+    let mut client = MockDynamoDbClient::new();
+    client.expect_put_item().returning(|_| Err(RusotoError::Service(PutItemError::ConditionalCheckFailed("failed".into()))));
+    client
+        .expect_get_item()
+        .returning(|_| Err(RusotoError::Service(GetItemError::ProvisionedThroughputExceeded("slow down".into()))));
+    client.expect_query().returning(|_| Err(RusotoError::Service(QueryError::ResourceNotFound("SingleTableName".into()))));
+
+    let err = client.put(Note { id: "note_1".into(), created_at: 1, updated_at: Some(1) }).execute().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConditionFailed);
+    assert!(!err.is_retryable());
+
+    let err = client.get::<Account>().id("acc_1").execute().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Throttled);
+    assert!(err.is_retryable());
+
+    let err = client.query::<UserPartition>().user_id("conrad").execute().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ResourceNotFound);
+
+    let err = nitroglycerin::DynamoError::<GetItemError>::Rusoto(RusotoError::HttpDispatch(HttpDispatchError::new("connection reset".into())));
+    assert_eq!(err.kind(), ErrorKind::Network);
+
+    let err = nitroglycerin::DynamoError::<GetItemError>::ParseError(nitroglycerin::AttributeError::MissingAttributes);
+    assert_eq!(err.kind(), ErrorKind::Parse);
+}
+
+#[test]
+fn test_stream_changes() {
+    use nitroglycerin::streams::{Change, Event, Removed, StreamError};
+
+    let event = r#"{
+        "Records": [
+            {
+                "eventID": "1",
+                "eventName": "INSERT",
+                "eventSource": "aws:dynamodb",
+                "dynamodb": {
+                    "Keys": { "id": { "S": "foo" }, "range": { "N": "1" } },
+                    "NewImage": { "id": { "S": "foo" }, "range": { "N": "1" }, "extra_values": { "L": [{ "S": "a" }] } },
+                    "SequenceNumber": "111",
+                    "StreamViewType": "NEW_AND_OLD_IMAGES"
+                }
+            },
+            {
+                "eventID": "2",
+                "eventName": "MODIFY",
+                "dynamodb": {
+                    "Keys": { "id": { "S": "foo" }, "range": { "N": "1" } },
+                    "OldImage": { "id": { "S": "foo" }, "range": { "N": "1" }, "extra_values": { "L": [{ "S": "a" }] } },
+                    "NewImage": { "id": { "S": "foo" }, "range": { "N": "1" }, "extra_values": { "L": [] } }
+                }
+            },
+            {
+                "eventID": "3",
+                "eventName": "REMOVE",
+                "dynamodb": {
+                    "Keys": { "id": { "S": "foo" }, "range": { "N": "1" } }
+                }
+            },
+            {
+                "eventID": "4",
+                "eventName": "MODIFY",
+                "dynamodb": {
+                    "Keys": { "id": { "S": "bar" }, "range": { "N": "2" } },
+                    "NewImage": { "id": { "S": "bar" }, "range": { "S": "2" }, "extra_values": { "L": [] } }
+                }
+            }
+        ]
+    }"#;
+
+    let item = |extra_values: Vec<String>| ExampleTable1 {
+        partition: "foo".into(),
+        sort: 1,
+        extra_values,
+    };
+
+    let mut changes = Event::from_json(event).unwrap().changes::<ExampleTable1>();
+    assert_eq!(changes.next().unwrap().unwrap(), Change::Insert(item(vec!["a".into()])));
+    assert_eq!(changes.next().unwrap().unwrap(), Change::Modify {
+        old: Some(item(vec!["a".into()])),
+        new: item(vec![]),
+    });
+    assert_eq!(changes.next().unwrap().unwrap(), Change::Remove(Removed::Key(m!(
+        "id" => av!(s: "foo"),
+        "range" => av!(n: "1"),
+    ))));
+
+    let err = changes.next().unwrap().unwrap_err();
+    assert!(matches!(err, StreamError::ParseError { image: "NewImage", .. }));
+    assert_eq!(
+        err.to_string(),
+        "could not parse NewImage of item {id=\"bar\", range=2}: at range: incorrect type: expected N, found S"
+    );
+    assert!(changes.next().is_none());
+}
+
+#[test]
+fn test_dynamodb_json() {
+    use nitroglycerin::json;
+
+    let nested: Attributes = m!("none" => av!(null: true),);
+    let item: Attributes = m!(
+        "id" => av!(s: "foo"),
+        "range" => av!(n: "1"),
+        "extra_values" => av!(l: vec![av!(s: "a"), av!(bool: true)]),
+        "tags" => av!(ss: vec!["x".into(), "y".into()]),
+        "data" => av!(b: b"hi".to_vec()),
+        "nested" => av!(m: nested),
+    );
+
+    let s = json::to_string(&item);
+    assert_eq!(
+        s,
+        r#"{"data":{"B":"aGk="},"extra_values":{"L":[{"S":"a"},{"BOOL":true}]},"id":{"S":"foo"},"nested":{"M":{"none":{"NULL":true}}},"range":{"N":"1"},"tags":{"SS":["x","y"]}}"#
+    );
+    assert_eq!(json::from_str(&s).unwrap(), item);
+    assert_eq!(json::from_str(&json::to_string_pretty(&item)).unwrap(), item);
+
+    // plain json numbers are accepted for N
+    assert_eq!(json::from_str(r#"{"range": {"N": 1}}"#).unwrap(), m!("range" => av!(n: "1"),));
+
+    let err = json::from_str(r#"{"extra_values": {"L": [{"S": "a"}, {"S": "b", "N": "1"}]}}"#).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "extra_values[1]");
+
+    let err = json::from_str(r#"{"id": {"X": "a"}}"#).unwrap_err();
+    assert_eq!(err.to_string(), "at id: could not parse value: unknown attribute type \"X\"");
+}
+
+#[test]
+fn test_plain_json() {
+    use nitroglycerin::json::{self, BigNumbers, Binary, PlainJson, Sets};
+    use serde_json::json;
+
+    let nested: Attributes = m!("none" => av!(null: true),);
+    let item: Attributes = m!(
+        "id" => av!(s: "foo"),
+        "joined" => av!(n: "1626900000"),
+        "score" => av!(n: "1.5"),
+        "balance" => av!(n: "12345678901234567890.123"),
+        "tags" => av!(ss: vec!["x".into()]),
+        "data" => av!(b: b"hi".to_vec()),
+        "active" => av!(bool: true),
+        "nested" => av!(m: nested),
+        "values" => av!(l: vec![av!(s: "a"), av!(n: "2")]),
+    );
+
+    assert_eq!(
+        json::to_plain_json(&item),
+        json!({
+            "id": "foo",
+            "joined": 1_626_900_000,
+            "score": 1.5,
+            "balance": "12345678901234567890.123",
+            "tags": ["x"],
+            "data": "aGk=",
+            "active": true,
+            "nested": { "none": null },
+            "values": ["a", 2],
+        })
+    );
+
+    let options = PlainJson::default().binary(Binary::Array).big_numbers(BigNumbers::Lossy);
+    let value = options.item_to_value(&item);
+    assert_eq!(value["data"], json!([104, 105]));
+    assert_eq!(value["balance"], json!(12_345_678_901_234_567_890.123));
+
+    let fixture = json!({
+        "id": "foo",
+        "joined": 1_626_900_000,
+        "tags": ["x", "y"],
+        "scores": [1, 2.5],
+        "values": ["a", 2],
+        "nested": { "none": null },
+    });
+    let parsed = json::from_plain_json(fixture.clone()).unwrap();
+    assert_eq!(parsed["joined"], av!(n: "1626900000"));
+    assert_eq!(parsed["tags"], av!(l: vec![av!(s: "x"), av!(s: "y")]));
+    assert_eq!(parsed["nested"].m.as_ref().unwrap()["none"], av!(null: true));
+
+    let parsed = PlainJson::default().sets(Sets::Infer).item_from_value(fixture).unwrap();
+    assert_eq!(parsed["tags"], av!(ss: vec!["x".to_owned(), "y".to_owned()]));
+    assert_eq!(parsed["scores"], av!(ns: vec!["1".to_owned(), "2.5".to_owned()]));
+    assert_eq!(parsed["values"], av!(l: vec![av!(s: "a"), av!(n: "2")]));
+
+    assert!(json::from_plain_json(json!(["not", "an", "item"])).is_err());
+}
+
+#[test]
+fn test_plain_json_numbers() {
+    use nitroglycerin::json::{NumericStrings, PlainJson, Sets};
+
+    let item: Attributes = m!(
+        "id" => av!(s: "foo"),
+        "joined" => av!(n: "1626900000"),
+        "score" => av!(n: "-1.25"),
+        "balance" => av!(n: "12345678901234567890.123"),
+        "ids" => av!(ns: vec!["1".to_owned(), "98765432109876543210.5".to_owned()]),
+    );
+
+    let options = PlainJson::default().sets(Sets::Infer).numeric_strings(NumericStrings::Number);
+    let line = options.item_to_value(&item).to_string();
+    assert!(line.contains("\"12345678901234567890.123\""));
+    let parsed = options.item_from_value(serde_json::from_str(&line).unwrap()).unwrap();
+    assert_eq!(parsed, item);
+
+    // numeric strings stay strings by default
+    let parsed = PlainJson::default().item_from_value(serde_json::from_str(&line).unwrap()).unwrap();
+    assert_eq!(parsed["balance"], av!(s: "12345678901234567890.123"));
+
+    // plain json numbers keep every digit
+    let value = serde_json::from_str(r#"{"big": 123456789012345678901234567890, "precise": 0.1000000000000000055511151231257827}"#).unwrap();
+    let parsed = PlainJson::default().item_from_value(value).unwrap();
+    assert_eq!(parsed["big"], av!(n: "123456789012345678901234567890"));
+    assert_eq!(parsed["precise"], av!(n: "0.1000000000000000055511151231257827"));
+}
+
+#[tokio::test]
+async fn test_export() {
+    use nitroglycerin::{
+        json::PlainJson,
+        transfer::{Format, Transfer, TransferError},
+    };
+
+    let mut client = MockDynamoDbClient::new();
+    client
+        .expect_scan()
+        .with(eq(ScanInput {
+            table_name: "ExampleTable1Name".into(),
+            ..Default::default()
+        }))
+        .returning(|_| {
+            Ok(ScanOutput {
+                items: Some(vec![m!(
+                    "id" => av!(s: "foo"),
+                    "range" => av!(n: "1"),
+                    "extra_values" => av!(l: vec![]),
+                )]),
+                last_evaluated_key: Some(m!(
+                    "id" => av!(s: "foo"),
+                    "range" => av!(n: "1"),
+                )),
+                ..Default::default()
+            })
+        });
+    client
+        .expect_scan()
+        .withf(|input| input.exclusive_start_key.is_some())
+        .returning(|_| {
+            Ok(ScanOutput {
+                items: Some(vec![m!(
+                    "id" => av!(s: "bar"),
+                    "range" => av!(n: "2"),
+                )]),
+                ..Default::default()
+            })
+        });
+
+    let mut lines = Vec::new();
+    let count = Transfer::default().format(Format::Plain(PlainJson::default())).export(&client, "ExampleTable1Name", &mut lines).await.unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(String::from_utf8(lines).unwrap(), "{\"extra_values\":[],\"id\":\"foo\",\"range\":1}\n{\"id\":\"bar\",\"range\":2}\n");
+
+    // the second item is missing `extra_values`, so it is not a valid `ExampleTable1`
+    let mut lines = Vec::new();
+    let err = Transfer::default().export_typed::<ExampleTable1, _, _>(&client, &mut lines).await.unwrap_err();
+    assert!(matches!(err, TransferError::InvalidItem { .. }));
+    assert_eq!(
+        String::from_utf8(lines).unwrap(),
+        "{\"extra_values\":{\"L\":[]},\"id\":{\"S\":\"foo\"},\"range\":{\"N\":\"1\"}}\n"
+    );
+}
+
+#[tokio::test]
+async fn test_import() {
+    use std::sync::{Arc, Mutex};
+
+    use nitroglycerin::{retry::RetryPolicy, transfer::Transfer};
+
+    let lines: String = (0..30).map(|i| format!("{{\"id\":{{\"S\":\"item_{}\"}}}}\n\n", i)).collect();
+
+    let written = Arc::new(Mutex::new(Vec::new()));
+    let mut client = MockDynamoDbClient::new();
+    let requests = written.clone();
+    client.expect_batch_write_item().returning(move |input| {
+        let mut batch = input.request_items["ExampleTable3Name"].clone();
+        assert!(batch.len() <= 25);
+        let mut requests = requests.lock().unwrap();
+        // leave the last item of the first batch unprocessed
+        let unprocessed = if requests.is_empty() { batch.pop() } else { None };
+        requests.extend(batch);
+        Ok(BatchWriteItemOutput {
+            unprocessed_items: unprocessed.map(|request| m!("ExampleTable3Name" => vec![request],)),
+            ..Default::default()
+        })
+    });
+
+    let transfer = Transfer::default().retry(RetryPolicy::default().base_delay(std::time::Duration::from_millis(1)).jitter(false));
+    let count = transfer.import(&client, "ExampleTable3Name", lines.as_bytes()).await.unwrap();
+    assert_eq!(count, 30);
+
+    {
+        let written = written.lock().unwrap();
+        assert_eq!(written.len(), 30);
+        assert_eq!(written[24].put_request.as_ref().unwrap().item["id"], av!(s: "item_24"));
+    }
+
+    let err = transfer.import(&client, "ExampleTable3Name", "{\"id\":{\"S\":\"a\"}}\nnot json\n".as_bytes()).await.unwrap_err();
+    assert_eq!(err.to_string().split(':').next().unwrap(), "could not parse line 2");
+}